@@ -49,6 +49,23 @@ impl Cursor {
         unsafe { Cursor { x: clang_getCanonicalCursor(self.x) } }
     }
 
+    pub fn raw_comment(&self) -> String {
+        unsafe { String_ { x: clang_Cursor_getRawCommentText(self.x) }.to_string() }
+    }
+
+    pub fn semantic_parent(&self) -> Cursor {
+        unsafe { Cursor { x: clang_getCursorSemanticParent(self.x) } }
+    }
+
+    pub fn mangling(&self) -> String {
+        unsafe { String_ { x: clang_Cursor_getMangling(self.x) }.to_string() }
+    }
+
+    // macro
+    pub fn is_macro_function_like(&self) -> bool {
+        unsafe { clang_Cursor_isMacroFunctionLike(self.x) != 0 }
+    }
+
     pub fn visit<F>(&self, func: F)
         where F: for<'a, 'b> FnMut(&'a Cursor, &'b Cursor) -> CXChildVisitResult
     {
@@ -69,6 +86,18 @@ impl Cursor {
         }
     }
 
+    // field
+    pub fn offset_of_field(&self) -> Option<usize> {
+        unsafe {
+            let offset = clang_Cursor_getOffsetOfField(self.x);
+            if offset < 0 {
+                None
+            } else {
+                Some(offset as usize)
+            }
+        }
+    }
+
     // enum
     pub fn enum_type(&self) -> Type {
         unsafe { Type { x: clang_getEnumDeclIntegerType(self.x) } }
@@ -107,6 +136,19 @@ impl Cursor {
     pub fn num_args(&self) -> i32 {
         unsafe { clang_Cursor_getNumArguments(self.x) }
     }
+
+    // method
+    pub fn method_is_static(&self) -> bool {
+        unsafe { clang_CXXMethod_isStatic(self.x) != 0 }
+    }
+
+    pub fn method_is_const(&self) -> bool {
+        unsafe { clang_CXXMethod_isConst(self.x) != 0 }
+    }
+
+    pub fn method_is_virtual(&self) -> bool {
+        unsafe { clang_CXXMethod_isVirtual(self.x) != 0 }
+    }
 }
 
 extern "C" fn visit_children<F>(cur: CXCursor,
@@ -0,0 +1,302 @@
+//! Selection of the globals to generate bindings for, from the allowlists and
+//! blocklists of `BindgenOptions`, of the types made opaque and of the style
+//! of the enums.
+
+use std::collections::HashSet;
+
+use regex::Regex;
+
+use types::*;
+use {BindgenOptions, ItemKind, Logger};
+
+/// Compiled name patterns of one kind of item, anchored at both ends.
+struct NameSet {
+    regexes: Vec<Regex>,
+}
+
+impl NameSet {
+    fn new(patterns: &[String], logger: &Logger) -> Result<NameSet, ()> {
+        let mut regexes = Vec::with_capacity(patterns.len());
+        for pat in patterns {
+            match Regex::new(&format!("^(?:{})$", pat)) {
+                Ok(re) => regexes.push(re),
+                Err(e) => {
+                    logger.error(&format!("Invalid regex `{}`: {}", pat, e));
+                    return Err(());
+                }
+            }
+        }
+        Ok(NameSet { regexes: regexes })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.regexes.is_empty()
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        self.regexes.iter().any(|re| re.is_match(name))
+    }
+}
+
+struct Filter {
+    allowed_functions: NameSet,
+    allowed_types: NameSet,
+    allowed_vars: NameSet,
+    blocked_functions: NameSet,
+    blocked_types: NameSet,
+    blocked_vars: NameSet,
+    opaque_types: NameSet,
+}
+
+impl Filter {
+    fn has_allowlist(&self) -> bool {
+        !self.allowed_functions.is_empty() || !self.allowed_types.is_empty() ||
+        !self.allowed_vars.is_empty()
+    }
+
+    fn is_allowed(&self, kind: ItemKind, name: &str) -> bool {
+        match kind {
+            ItemKind::Function => self.allowed_functions.matches(name),
+            ItemKind::Type => self.allowed_types.matches(name),
+            ItemKind::Var => self.allowed_vars.matches(name),
+        }
+    }
+
+    fn is_blocked(&self, kind: ItemKind, name: &str) -> bool {
+        match kind {
+            ItemKind::Function => self.blocked_functions.matches(name),
+            ItemKind::Type => self.blocked_types.matches(name),
+            ItemKind::Var => self.blocked_vars.matches(name),
+        }
+    }
+}
+
+/// Returns the kind and the name of a global, `None` for `GOther`.
+fn global_name(g: &Global) -> Option<(ItemKind, String)> {
+    match *g {
+        GType(ref ti) |
+        GMacroGroup(ref ti, _) => Some((ItemKind::Type, ti.borrow().name.clone())),
+        GComp(ref ci) |
+        GCompDecl(ref ci) => Some((ItemKind::Type, ci.borrow().name.clone())),
+        GEnum(ref ei) |
+        GEnumDecl(ref ei) => Some((ItemKind::Type, ei.borrow().name.clone())),
+        GVar(ref vi) => Some((ItemKind::Var, vi.borrow().name.clone())),
+        GFunc(ref vi) => Some((ItemKind::Function, vi.borrow().name.clone())),
+        GMacro(ref mi) => Some((ItemKind::Function, mi.borrow().name.clone())),
+        GOther => None,
+    }
+}
+
+/// The address of the info shared by a global and the types referring to it.
+fn global_key(g: &Global) -> Option<usize> {
+    match *g {
+        GType(ref ti) |
+        GMacroGroup(ref ti, _) => Some(&*ti.borrow() as *const TypeInfo as usize),
+        GComp(ref ci) |
+        GCompDecl(ref ci) => Some(&*ci.borrow() as *const CompInfo as usize),
+        GEnum(ref ei) |
+        GEnumDecl(ref ei) => Some(&*ei.borrow() as *const EnumInfo as usize),
+        GVar(ref vi) |
+        GFunc(ref vi) => Some(&*vi.borrow() as *const VarInfo as usize),
+        GMacro(ref mi) => Some(&*mi.borrow() as *const MacroInfo as usize),
+        GOther => None,
+    }
+}
+
+/// Walks a type, recording the typedefs, structs, unions and enums it
+/// transitively depends on in `used`.
+fn collect_type(ty: &Type, used: &mut HashSet<usize>) {
+    match *ty {
+        TPtr(ref t, _, _) |
+        TArray(ref t, _, _) |
+        TVector(ref t, _, _) |
+        TFlexArray(ref t) |
+        TAtomic(ref t, _) => collect_type(t, used),
+        TFuncProto(ref sig, _) |
+        TFuncPtr(ref sig, _) => collect_sig(sig, used),
+        TNamed(ref ti) => {
+            let ti = ti.borrow();
+            if used.insert(&*ti as *const TypeInfo as usize) && !ti.opaque {
+                collect_type(&ti.ty, used);
+            }
+        }
+        TComp(ref ci) => {
+            let ci = ci.borrow();
+            if used.insert(&*ci as *const CompInfo as usize) && !ci.opaque {
+                collect_members(&ci.members, used);
+                for method in &ci.methods {
+                    collect_sig(&method.sig, used);
+                }
+            }
+        }
+        TEnum(ref ei) => {
+            used.insert(&*ei.borrow() as *const EnumInfo as usize);
+        }
+        TVoid | TInt(..) | TFloat(..) | TComplex(..) => {}
+    }
+}
+
+fn collect_sig(sig: &FuncSig, used: &mut HashSet<usize>) {
+    collect_type(&sig.ret_ty, used);
+    for &(_, ref t) in &sig.args {
+        collect_type(t, used);
+    }
+}
+
+fn collect_members(members: &[CompMember], used: &mut HashSet<usize>) {
+    for m in members {
+        match *m {
+            CompMember::Field(ref f) => collect_type(&f.ty, used),
+            CompMember::Comp(ref ci) => collect_type(&TComp(ci.clone()), used),
+            CompMember::Enum(ref ei) => collect_type(&TEnum(ei.clone()), used),
+            CompMember::CompField(ref ci, ref f) => {
+                collect_type(&TComp(ci.clone()), used);
+                collect_type(&f.ty, used);
+            }
+            CompMember::EnumField(ref ei, ref f) => {
+                collect_type(&TEnum(ei.clone()), used);
+                collect_type(&f.ty, used);
+            }
+        }
+    }
+}
+
+/// Collects the types a global depends on.
+fn collect_global(g: &Global, used: &mut HashSet<usize>) {
+    match *g {
+        GType(ref ti) |
+        GMacroGroup(ref ti, _) => collect_type(&TNamed(ti.clone()), used),
+        GComp(ref ci) |
+        GCompDecl(ref ci) => collect_type(&TComp(ci.clone()), used),
+        GEnum(ref ei) |
+        GEnumDecl(ref ei) => collect_type(&TEnum(ei.clone()), used),
+        GVar(ref vi) |
+        GFunc(ref vi) => collect_type(&vi.borrow().ty, used),
+        GMacro(_) | GOther => {}
+    }
+}
+
+/// Marks the structs, unions and typedefs matching `opaque_types` as opaque.
+fn mark_opaque(opaque_types: &NameSet, globals: &[Global]) {
+    for g in globals {
+        match *g {
+            GComp(ref ci) => {
+                let mut ci = ci.borrow_mut();
+                if !ci.name.is_empty() && opaque_types.matches(&ci.name) {
+                    ci.opaque = true;
+                }
+            }
+            GType(ref ti) => {
+                let mut ti = ti.borrow_mut();
+                if !opaque_types.matches(&ti.name) {
+                    continue;
+                }
+                // `typedef struct {...} name;` is generated as the struct.
+                let unnamed_comp = match ti.ty {
+                    TComp(ref ci) if ci.borrow().name.is_empty() => Some(ci.clone()),
+                    _ => None,
+                };
+                match unnamed_comp {
+                    Some(ci) => ci.borrow_mut().opaque = true,
+                    None => ti.opaque = true,
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Sets the style of the enums matching `newtype_enums`,
+/// `constified_enum_modules` or `bitfield_enums`, by name or by the name of
+/// the typedef of an unnamed enum.
+pub fn mark_enum_styles(options: &BindgenOptions,
+                        logger: &Logger,
+                        globals: &[Global])
+                        -> Result<(), ()> {
+    let styles = [(try!(NameSet::new(&options.newtype_enums, logger)), EnumStyle::Newtype),
+                  (try!(NameSet::new(&options.constified_enum_modules, logger)),
+                   EnumStyle::ModuleConsts),
+                  (try!(NameSet::new(&options.bitfield_enums, logger)), EnumStyle::Bitfield)];
+    if styles.iter().all(|&(ref names, _)| names.is_empty()) {
+        return Ok(());
+    }
+
+    for g in globals {
+        let (ei, name) = match *g {
+            GEnum(ref ei) if !ei.borrow().name.is_empty() => (ei.clone(), ei.borrow().name.clone()),
+            GType(ref ti) => {
+                let ti = ti.borrow();
+                match ti.ty {
+                    TEnum(ref ei) if ei.borrow().name.is_empty() => (ei.clone(), ti.name.clone()),
+                    _ => continue,
+                }
+            }
+            _ => continue,
+        };
+        if let Some(&(_, style)) = styles.iter().find(|&&(ref names, _)| names.matches(&name)) {
+            ei.borrow_mut().style = Some(style);
+        }
+    }
+    Ok(())
+}
+
+/// Removes the blocklisted globals and, if there is an allowlist, keeps only
+/// the allowlisted globals and the types they transitively depend on. The
+/// fields of the opaque types are not followed.
+pub fn filter_globals(options: &BindgenOptions,
+                      logger: &Logger,
+                      globals: Vec<Global>)
+                      -> Result<Vec<Global>, ()> {
+    let filter = Filter {
+        allowed_functions: try!(NameSet::new(&options.allowlist_functions, logger)),
+        allowed_types: try!(NameSet::new(&options.allowlist_types, logger)),
+        allowed_vars: try!(NameSet::new(&options.allowlist_vars, logger)),
+        blocked_functions: try!(NameSet::new(&options.blocklist_functions, logger)),
+        blocked_types: try!(NameSet::new(&options.blocklist_types, logger)),
+        blocked_vars: try!(NameSet::new(&options.blocklist_vars, logger)),
+        opaque_types: try!(NameSet::new(&options.opaque_types, logger)),
+    };
+    mark_opaque(&filter.opaque_types, &globals);
+
+    let is_blocked = |g: &Global| {
+        match global_name(g) {
+            Some((kind, name)) => filter.is_blocked(kind, &name),
+            None => false,
+        }
+    };
+
+    // The blocklisted types are marked as used up front so that the types
+    // only they depend on are not pulled in.
+    let mut used = HashSet::new();
+    let globals: Vec<_> = globals.into_iter()
+                                 .filter(|g| {
+                                     if !is_blocked(g) {
+                                         return true;
+                                     }
+                                     if let Some(key) = global_key(g) {
+                                         used.insert(key);
+                                     }
+                                     false
+                                 })
+                                 .collect();
+    if !filter.has_allowlist() {
+        return Ok(globals);
+    }
+
+    for g in &globals {
+        let allowed = match global_name(g) {
+            Some((kind, name)) => !name.is_empty() && filter.is_allowed(kind, &name),
+            None => false,
+        };
+        if allowed {
+            collect_global(g, &mut used);
+            if let Some(key) = global_key(g) {
+                used.insert(key);
+            }
+        }
+    }
+
+    Ok(globals.into_iter()
+              .filter(|g| global_key(g).map_or(false, |key| used.contains(&key)))
+              .collect())
+}
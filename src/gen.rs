@@ -1,9 +1,11 @@
 use std;
+use std::cmp;
+use std::fs;
 use std::mem;
 use std::cell::RefCell;
 use std::vec::Vec;
 use std::rc::Rc;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ascii::AsciiExt;
 
 use syntax::abi;
@@ -20,13 +22,29 @@ use syntax::attr::mk_attr_id;
 use syntax::ptr::P;
 use syntax::print::pprust::tts_to_string;
 
-use super::{BindgenOptions, LinkType};
+use super::{BindgenOptions, FnMacroStyle, ItemKind, LinkType, Logger, ParseCallbacks};
+use super::clang_target;
 use types::*;
 
 struct GenCtx<'r> {
     ext_cx: base::ExtCtxt<'r>,
+    logger: &'r (Logger + 'r),
     unnamed_ty: usize,
     span: Span,
+    /// The C++ namespace of the module being generated.
+    namespace: Vec<String>,
+    /// The blobs standing for the floats without a Rust equivalent, to
+    /// define at the root of the bindings.
+    float_blobs: Vec<(FKind, Layout)>,
+    /// The alignments of the vector wrappers to define at the root of the
+    /// bindings.
+    vector_aligns: Vec<usize>,
+    /// Whether the marker of the flexible array members must be defined at
+    /// the root of the bindings.
+    flex_array: bool,
+    /// Whether the complex number struct must be defined at the root of the
+    /// bindings.
+    complex: bool,
 }
 
 fn ref_eq<T>(thing: &T, other: &T) -> bool {
@@ -55,6 +73,9 @@ fn rust_id(ctx: &mut GenCtx, mut name: &str, remove_prefix: &str) -> (String, bo
 
 const UNNAMED_PREFIX: &'static str = "Unnamed";
 
+/// The attribute marking the structs which stand for native Rust unions.
+pub const UNION_MARKER: &'static str = "bindgen_union";
+
 fn unnamed_name(ctx: &mut GenCtx, name: &str) -> String {
     if name.is_empty() {
         ctx.unnamed_ty += 1;
@@ -119,8 +140,20 @@ fn extract_definitions(ctx: &mut GenCtx,
                         rust_id(ctx, &t.name, &options.remove_prefix).0 == rust_id(ctx, alias, &options.remove_prefix).0)
                     // important: need to end borrow of n, c, e, here
                 };
-                if !is_cyclic {
-                    defs.extend(ctypedef_to_rs(ctx, options, options.derive_debug, &t.name, &t.ty))
+                if t.opaque {
+                    defs.extend(opaque_blob_to_rs(ctx,
+                                                  &t.name,
+                                                  t.layout,
+                                                  t.ty.is_non_trivial_class(),
+                                                  &t.comment,
+                                                  options));
+                } else if !is_cyclic {
+                    defs.extend(ctypedef_to_rs(ctx,
+                                               options,
+                                               options.derive_debug,
+                                               &t.name,
+                                               &t.ty,
+                                               &t.comment))
                 }
             }
             GCompDecl(ref ci) => {
@@ -137,14 +170,26 @@ fn extract_definitions(ctx: &mut GenCtx,
                     c.name = unnamed_name(ctx, &c.name);
                 }
                 let c = ci.borrow().clone();
-                defs.extend(comp_to_rs(ctx,
-                                       c.kind,
-                                       comp_name(c.kind, &c.name),
-                                       options,
-                                       options.derive_debug,
-                                       c.layout,
-                                       c.members)
-                                .into_iter())
+                if c.opaque {
+                    defs.extend(opaque_blob_to_rs(ctx,
+                                                  &comp_name(c.kind, &c.name),
+                                                  c.layout,
+                                                  c.non_trivial,
+                                                  &c.comment,
+                                                  options));
+                } else {
+                    defs.extend(comp_to_rs(ctx,
+                                           c.kind,
+                                           comp_name(c.kind, &c.name),
+                                           options,
+                                           options.derive_debug,
+                                           c.layout,
+                                           c.non_trivial,
+                                           c.members,
+                                           &c.comment)
+                                    .into_iter());
+                }
+                defs.extend(cxx_methods_to_rs(ctx, ci, options));
             }
             GEnumDecl(ref ei) => {
                 {
@@ -166,12 +211,25 @@ fn extract_definitions(ctx: &mut GenCtx,
                                         &enum_name(&e.name),
                                         e.kind,
                                         e.layout,
-                                        &e.items));
+                                        &e.items,
+                                        e.effective_style(options.rust_enums),
+                                        &e.comment));
+            }
+            GMacro(ref mi) => defs.push(fn_macro_to_rs(ctx, &mi.borrow(), options)),
+            GMacroGroup(ref ti, ref items) => {
+                defs.extend(macro_group_to_rs(ctx, &ti.borrow(), items, options));
             }
             GVar(ref vi) => {
                 let v = vi.borrow();
                 let ty = cty_to_rs(ctx, &v.ty, options);
-                defs.push(const_to_rs(ctx, &v.name, v.val.unwrap(), ty, options));
+                let name = callback_item_name(options, &v.name, ItemKind::Var)
+                               .unwrap_or_else(|| v.name.clone());
+                defs.push(const_to_rs(ctx,
+                                      &name,
+                                      v.val.as_ref().unwrap(),
+                                      ty,
+                                      &v.comment,
+                                      options));
             }
             _ => {}
         }
@@ -180,6 +238,69 @@ fn extract_definitions(ctx: &mut GenCtx,
     defs
 }
 
+fn static_wrapper_name(name: &str) -> String {
+    format!("{}__extern", name)
+}
+
+/// Generates the C source defining an exported wrapper for every `static`
+/// function, to be compiled and linked with the bindings.
+pub fn gen_static_wrappers(globs: &[Global]) -> String {
+    let mut headers: Vec<String> = vec![];
+    let mut names = HashSet::new();
+    let mut wrappers = String::new();
+    for g in globs {
+        let vi = match *g {
+            GFunc(ref vi) => vi.borrow(),
+            _ => continue,
+        };
+        let (s, returns) = match (vi.static_fn.as_ref(), &vi.ty) {
+            (Some(s), &TFuncPtr(ref sig, _)) => (s, *sig.ret_ty != TVoid),
+            _ => continue,
+        };
+        if !names.insert(vi.name.clone()) {
+            continue;
+        }
+        if !headers.contains(&s.header) {
+            headers.push(s.header.clone());
+        }
+
+        // `__typeof__` spares us from splitting the declarators around the
+        // argument names, like for function pointers.
+        let params: Vec<_> = s.arg_tys
+                              .iter()
+                              .enumerate()
+                              .map(|(i, ty)| format!("__typeof__({}) arg{}", ty, i + 1))
+                              .collect();
+        let args: Vec<_> = (1..s.arg_tys.len() + 1).map(|i| format!("arg{}", i)).collect();
+        let params = if params.is_empty() {
+            "void".to_owned()
+        } else {
+            params.join(", ")
+        };
+        wrappers.push_str(&format!("__typeof__({ret_ty}) {wrapper}({params}) {{\n    \
+                                    {ret}{name}({args});\n}}\n",
+                                   ret_ty = s.ret_ty,
+                                   wrapper = static_wrapper_name(&vi.name),
+                                   params = params,
+                                   ret = if returns { "return " } else { "" },
+                                   name = vi.name,
+                                   args = args.join(", ")));
+    }
+
+    let mut source = String::from("/* automatically generated by rust-bindgen */\n\n");
+    for header in headers {
+        // The wrappers are compiled from another directory than the one the
+        // headers were parsed from.
+        let header = fs::canonicalize(&header)
+                         .map(|path| path.display().to_string())
+                         .unwrap_or(header);
+        source.push_str(&format!("#include \"{}\"\n", header));
+    }
+    source.push('\n');
+    source.push_str(&wrappers);
+    source
+}
+
 fn extract_functions(ctx: &mut GenCtx,
                      fs: &[Global],
                      options: &BindgenOptions)
@@ -189,14 +310,33 @@ fn extract_functions(ctx: &mut GenCtx,
             GFunc(ref vi) => {
                 let v = vi.borrow();
                 match v.ty {
+                    TFuncPtr(ref sig, _) if passes_non_trivial_class(sig) => {
+                        let msg = format!("Skipping the function `{}`, which passes a C++ class \
+                                           with a non-trivial destructor or copy constructor \
+                                           by value.",
+                                          v.name);
+                        ctx.logger.warn(&msg[..]);
+                        None
+                    }
                     TFuncPtr(ref sig, _) => {
+                        let link_name = v.static_fn
+                                         .as_ref()
+                                         .map(|_| static_wrapper_name(&v.name))
+                                         .or_else(|| v.mangled_name.clone());
+                        let (name, link_name) =
+                            match callback_item_name(options, &v.name, ItemKind::Function) {
+                                Some(name) => (name, link_name.or_else(|| Some(v.name.clone()))),
+                                None => (v.name.clone(), link_name),
+                            };
                         let decl = cfunc_to_rs(ctx,
-                                               v.name.clone(),
+                                               name,
+                                               link_name,
                                                &*sig.ret_ty,
                                                &sig.args[..],
                                                sig.is_variadic,
+                                               &v.comment,
                                                options);
-                        (sig.abi, decl)
+                        Some((sig.abi, decl))
                     }
                     _ => unreachable!(),
                 }
@@ -206,12 +346,18 @@ fn extract_functions(ctx: &mut GenCtx,
     });
 
     let mut map = HashMap::new();
-    for (abi, func) in func_list {
+    for (abi, func) in func_list.filter_map(|f| f) {
         map.entry(abi).or_insert_with(Vec::new).push(func);
     }
     map
 }
 
+/// Whether the function passes or returns by value a C++ class that the C++
+/// ABI passes through a hidden pointer, which its binding can't do.
+fn passes_non_trivial_class(sig: &FuncSig) -> bool {
+    sig.ret_ty.is_non_trivial_class() || sig.args.iter().any(|&(_, ref t)| t.is_non_trivial_class())
+}
+
 /// Converts `typedef struct {...} Test` to rust `struct Test {...}`
 fn remove_unnamed(globals: &mut Vec<Global>) {
     let mut i = 1;
@@ -251,6 +397,7 @@ fn remove_unnamed(globals: &mut Vec<Global>) {
 }
 
 pub fn gen_mod(options: &BindgenOptions,
+               logger: &Logger,
                globs: Vec<Global>,
                span: Span)
                -> (Vec<P<ast::Item>>, Vec<ast::Attribute>) {
@@ -269,8 +416,14 @@ pub fn gen_mod(options: &BindgenOptions,
                                    Vec::new(),
                                    cfg,
                                    &mut macro_loader),
+        logger: logger,
         unnamed_ty: 0,
         span: span,
+        namespace: vec![],
+        float_blobs: vec![],
+        vector_aligns: vec![],
+        flex_array: false,
+        complex: false,
     };
     ctx.ext_cx.bt_push(ExpnInfo {
         call_site: ctx.span,
@@ -280,6 +433,183 @@ pub fn gen_mod(options: &BindgenOptions,
             span: None,
         },
     });
+    if let Some(ref callbacks) = options.parse_callbacks {
+        rename_types(&globs, &**callbacks);
+    }
+    let mut defs = gen_namespace(&mut ctx, options, globs);
+    for (kind, layout) in mem::replace(&mut ctx.float_blobs, vec![]) {
+        defs.extend(opaque_blob_to_rs(&mut ctx, float_blob_name(kind), layout, false, "", options));
+    }
+    let mut vector_aligns = mem::replace(&mut ctx.vector_aligns, vec![]);
+    vector_aligns.sort();
+    for align in vector_aligns {
+        defs.push(vector_wrapper_to_rs(&mut ctx, align));
+    }
+    if ctx.flex_array {
+        defs.extend(flex_array_marker_to_rs(&mut ctx, options));
+    }
+    if ctx.complex {
+        defs.push(complex_to_rs(&mut ctx));
+    }
+
+    // let attrs = vec!(mk_attr_list(&mut ctx, "allow", ));
+    let mod_attrs = vec![mk_attr_style(&mut ctx,
+                                       "allow",
+                                       &["dead_code",
+                                         "non_camel_case_types",
+                                         "non_upper_case_globals",
+                                         "non_snake_case"],
+                                       ast::AttrStyle::Inner)];
+    (defs, mod_attrs)
+}
+
+/// Applies `ParseCallbacks::item_name` to the named types and
+/// `ParseCallbacks::field_name` to the fields of the structs and unions,
+/// before the references to them are generated.
+fn rename_types(globs: &[Global], callbacks: &ParseCallbacks) {
+    let mut renamed = HashSet::new();
+    let rename_type = |name: &mut String| {
+        if !name.is_empty() {
+            if let Some(new_name) = callbacks.item_name(name, ItemKind::Type) {
+                *name = new_name;
+            }
+        }
+    };
+    for g in globs {
+        match *g {
+            GType(ref ti) |
+            GMacroGroup(ref ti, _) => {
+                let mut ti = ti.borrow_mut();
+                if renamed.insert(&*ti as *const TypeInfo as usize) {
+                    rename_type(&mut ti.name);
+                }
+            }
+            GComp(ref ci) |
+            GCompDecl(ref ci) => {
+                let mut c = ci.borrow_mut();
+                if renamed.insert(&*c as *const CompInfo as usize) {
+                    let parent = c.name.clone();
+                    rename_fields(&mut c.members, &parent, callbacks);
+                    rename_type(&mut c.name);
+                }
+            }
+            GEnum(ref ei) |
+            GEnumDecl(ref ei) => {
+                let mut e = ei.borrow_mut();
+                if renamed.insert(&*e as *const EnumInfo as usize) {
+                    rename_type(&mut e.name);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Applies `ParseCallbacks::field_name` to the fields of a struct or union,
+/// including the ones of its unnamed nested structs and unions.
+fn rename_fields(members: &mut [CompMember], parent: &str, callbacks: &ParseCallbacks) {
+    let rename_field = |f: &mut FieldInfo| {
+        if let Some(new_name) = callbacks.field_name(parent, &f.name) {
+            f.name = new_name;
+        }
+        if let Some(ref mut bitfields) = f.bitfields {
+            for bitfield in bitfields {
+                if let Some(new_name) = callbacks.field_name(parent, &bitfield.name) {
+                    bitfield.name = new_name;
+                }
+            }
+        }
+    };
+    for m in members {
+        match *m {
+            CompMember::Field(ref mut f) |
+            CompMember::EnumField(_, ref mut f) => rename_field(f),
+            CompMember::CompField(ref ci, ref mut f) => {
+                rename_field(f);
+                let mut c = ci.borrow_mut();
+                if c.name.is_empty() {
+                    rename_fields(&mut c.members, parent, callbacks);
+                }
+            }
+            CompMember::Comp(ref ci) => {
+                let mut c = ci.borrow_mut();
+                if c.name.is_empty() {
+                    rename_fields(&mut c.members, parent, callbacks);
+                }
+            }
+            CompMember::Enum(_) => {}
+        }
+    }
+}
+
+/// The name given by `ParseCallbacks::item_name` to a function or variable.
+fn callback_item_name(options: &BindgenOptions, name: &str, kind: ItemKind) -> Option<String> {
+    options.parse_callbacks.as_ref().and_then(|callbacks| callbacks.item_name(name, kind))
+}
+
+/// The C++ namespace of a global, empty for C declarations.
+fn global_namespace(g: &Global) -> Vec<String> {
+    match *g {
+        GType(ref ti) |
+        GMacroGroup(ref ti, _) => ti.borrow().namespace.clone(),
+        GComp(ref ci) |
+        GCompDecl(ref ci) => ci.borrow().namespace.clone(),
+        GEnum(ref ei) |
+        GEnumDecl(ref ei) => ei.borrow().namespace.clone(),
+        GVar(ref vi) |
+        GFunc(ref vi) => vi.borrow().namespace.clone(),
+        GMacro(_) | GOther => vec![],
+    }
+}
+
+/// Generates the items of the namespace `ctx.namespace`, the nested
+/// namespaces becoming nested modules.
+fn gen_namespace(ctx: &mut GenCtx,
+                 options: &BindgenOptions,
+                 globs: Vec<Global>)
+                 -> Vec<P<ast::Item>> {
+    let depth = ctx.namespace.len();
+    let mut own = vec![];
+    let mut nested: Vec<(String, Vec<Global>)> = vec![];
+    for g in globs {
+        match global_namespace(&g).get(depth).cloned() {
+            None => own.push(g),
+            Some(name) => {
+                match nested.iter().position(|&(ref n, _)| *n == name) {
+                    Some(i) => nested[i].1.push(g),
+                    None => nested.push((name, vec![g])),
+                }
+            }
+        }
+    }
+
+    let mut defs = gen_globals(ctx, options, own);
+    for (name, globs) in nested {
+        ctx.namespace.push(name.clone());
+        let items = gen_namespace(ctx, options, globs);
+        ctx.namespace.pop();
+
+        let id = rust_id(ctx, &name, "").0;
+        defs.push(P(ast::Item {
+            ident: ctx.ext_cx.ident_of(&id),
+            attrs: vec![],
+            id: ast::DUMMY_NODE_ID,
+            node: ast::ItemKind::Mod(ast::Mod {
+                inner: ctx.span,
+                items: items,
+            }),
+            vis: ast::Visibility::Public,
+            span: ctx.span,
+        }));
+    }
+    defs
+}
+
+/// Generates the items of the globals of a single namespace.
+fn gen_globals(ctx: &mut GenCtx,
+               options: &BindgenOptions,
+               globs: Vec<Global>)
+               -> Vec<P<ast::Item>> {
     let uniq_globs = tag_dup_decl(&globs);
 
     let mut fs = vec![];
@@ -311,39 +641,36 @@ pub fn gen_mod(options: &BindgenOptions,
 
     gs = remove_redundant_decl(gs);
     remove_unnamed(&mut gs);
-    let mut defs = extract_definitions(&mut ctx, options, &gs);
+    let mut defs = extract_definitions(ctx, options, &gs);
 
     let vars = vs.into_iter()
                  .map(|v| {
                      match v {
                          GVar(vi) => {
                              let v = vi.borrow();
-                             cvar_to_rs(&mut ctx, v.name.clone(), &v.ty, v.is_const, options)
+                             cvar_to_rs(ctx,
+                                        v.name.clone(),
+                                        &v.ty,
+                                        v.is_const,
+                                        &v.comment,
+                                        options)
                          }
                          _ => unreachable!(),
                      }
                  })
                  .collect();
 
-    let funcs = extract_functions(&mut ctx, &fs, options);
+    let funcs = extract_functions(ctx, &fs, options);
 
     if !Vec::is_empty(&vars) {
-        defs.push(mk_extern(&mut ctx, &options.links, vars, abi::Abi::C));
+        defs.push(mk_extern(ctx, &options.links, vars, abi::Abi::C));
     }
 
     for (abi, funcs) in funcs.into_iter() {
-        defs.push(mk_extern(&mut ctx, &options.links, funcs, abi));
+        defs.push(mk_extern(ctx, &options.links, funcs, abi));
     }
 
-    // let attrs = vec!(mk_attr_list(&mut ctx, "allow", ));
-    let mod_attrs = vec![mk_attr_style(&mut ctx,
-                                       "allow",
-                                       &["dead_code",
-                                         "non_camel_case_types",
-                                         "non_upper_case_globals",
-                                         "non_snake_case"],
-                                       ast::AttrStyle::Inner)];
-    (defs, mod_attrs)
+    defs
 }
 
 fn mk_extern(ctx: &mut GenCtx,
@@ -499,7 +826,8 @@ fn ctypedef_to_rs(ctx: &mut GenCtx,
                   options: &BindgenOptions,
                   derive_debug: bool,
                   name: &str,
-                  ty: &Type)
+                  ty: &Type,
+                  comment: &str)
                   -> Vec<P<ast::Item>> {
     let mk_item = |ctx: &mut GenCtx, name: &str, ty: &Type| -> P<ast::Item> {
         let rust_ty = match &name[..] {
@@ -529,7 +857,7 @@ fn ctypedef_to_rs(ctx: &mut GenCtx,
 
         P(ast::Item {
             ident: ctx.ext_cx.ident_of(&rust_name),
-            attrs: Vec::new(),
+            attrs: mk_doc_attrs(ctx, comment, options),
             id: ast::DUMMY_NODE_ID,
             node: base,
             vis: ast::Visibility::Public,
@@ -543,13 +871,20 @@ fn ctypedef_to_rs(ctx: &mut GenCtx,
             if is_empty {
                 ci.borrow_mut().name = name.into();
                 let c = ci.borrow().clone();
+                let comment = if c.comment.is_empty() {
+                    comment
+                } else {
+                    &c.comment[..]
+                };
                 comp_to_rs(ctx,
                            c.kind,
                            name.into(),
                            options,
                            derive_debug,
                            c.layout,
-                           c.members)
+                           c.non_trivial,
+                           c.members,
+                           comment)
             } else {
                 vec![mk_item(ctx, name, ty)]
             }
@@ -559,7 +894,20 @@ fn ctypedef_to_rs(ctx: &mut GenCtx,
             if is_empty {
                 ei.borrow_mut().name = name.into();
                 let e = ei.borrow();
-                cenum_to_rs(ctx, options, derive_debug, name, e.kind, e.layout, &e.items)
+                let comment = if e.comment.is_empty() {
+                    comment
+                } else {
+                    &e.comment[..]
+                };
+                cenum_to_rs(ctx,
+                            options,
+                            derive_debug,
+                            name,
+                            e.kind,
+                            e.layout,
+                            &e.items,
+                            e.effective_style(options.rust_enums),
+                            comment)
             } else {
                 vec![mk_item(ctx, name, ty)]
             }
@@ -569,23 +917,46 @@ fn ctypedef_to_rs(ctx: &mut GenCtx,
 }
 
 /// Converts a C composed type (struct or union) to Rust AST Items.
+#[cfg_attr(feature = "clippy", allow(too_many_arguments))]
 fn comp_to_rs(ctx: &mut GenCtx,
               kind: CompKind,
               name: String,
               options: &BindgenOptions,
               derive_debug: bool,
               layout: Layout,
-              members: Vec<CompMember>)
+              non_trivial: bool,
+              members: Vec<CompMember>,
+              comment: &str)
               -> Vec<P<ast::Item>> {
+    if !layout.is_representable() {
+        let msg = format!("{} is both packed and aligned, which Rust can't express. It is made \
+                           opaque.",
+                          name);
+        ctx.logger.warn(&msg[..]);
+        return opaque_blob_to_rs(ctx, &name, layout, non_trivial, comment, options);
+    }
     match kind {
-        CompKind::Struct => cstruct_to_rs(ctx, &name, options, derive_debug, layout, members),
-        CompKind::Union => cunion_to_rs(ctx, name, options, derive_debug, layout, members),
+        CompKind::Struct => {
+            cstruct_to_rs(ctx,
+                          &name,
+                          options,
+                          derive_debug,
+                          layout,
+                          non_trivial,
+                          members,
+                          comment)
+        }
+        CompKind::Union => cunion_to_rs(ctx, name, options, derive_debug, layout, members, comment),
     }
 }
 
+/// Generates the private fields filling exactly `padding_size` bytes from the
+/// byte `offset` of a struct with the given layout.
 fn gen_padding_fields(ctx: &mut GenCtx,
                       idx: usize,
-                      padding_size: usize)
+                      offset: usize,
+                      padding_size: usize,
+                      layout: Layout)
                       -> Vec<ast::StructField> {
     const MAX_ARRAY_CLONE_LEN: usize = 32; // impl<T: Copy> Clone for [T; 32]
 
@@ -595,20 +966,30 @@ fn gen_padding_fields(ctx: &mut GenCtx,
     let u8_ty = P(mk_ty(ctx, false, vec!["u8".to_owned()]));
 
     let mut fields = vec![];
-    if padding_size <= MAX_ARRAY_CLONE_LEN {
-        // Use u8s if possible
-        fields.push((&u8_ty, padding_size));
-    } else {
-        // u64s only; subtract implicit 8-byte pad for alignment
-        let size = padding_size - (padding_size % u64_size);
-        (0..(size / max_field_size))
-            .map(|_| (&u64_ty, MAX_ARRAY_CLONE_LEN))
-            .collect::<Vec<(&P<ast::Ty>, usize)>>();
-
-        let u64_num = (size % max_field_size) / u64_size;
-        if u64_num > 0 {
-            fields.push((&u64_ty, u64_num));
+    let mut remaining = padding_size;
+    // u64s keep big paddings short, but must not change the struct alignment.
+    if remaining > MAX_ARRAY_CLONE_LEN && layout.align >= u64_size {
+        // The u64s are aligned implicitly unless the struct is packed.
+        let head = (u64_size - offset % u64_size) % u64_size;
+        if head > 0 && layout.pack.map_or(false, |p| p < u64_size) {
+            fields.push((&u8_ty, head));
         }
+        remaining -= head;
+        for _ in 0..(remaining / max_field_size) {
+            fields.push((&u64_ty, MAX_ARRAY_CLONE_LEN));
+        }
+        remaining %= max_field_size;
+        if remaining >= u64_size {
+            fields.push((&u64_ty, remaining / u64_size));
+            remaining %= u64_size;
+        }
+    }
+    while remaining > MAX_ARRAY_CLONE_LEN {
+        fields.push((&u8_ty, MAX_ARRAY_CLONE_LEN));
+        remaining -= MAX_ARRAY_CLONE_LEN;
+    }
+    if remaining > 0 {
+        fields.push((&u8_ty, remaining));
     }
 
     fields.iter()
@@ -630,13 +1011,47 @@ fn gen_padding_fields(ctx: &mut GenCtx,
           .collect()
 }
 
+/// Rounds `offset` up to the next multiple of `align`.
+fn align_to(offset: usize, align: usize) -> usize {
+    if align == 0 {
+        offset
+    } else {
+        (offset + align - 1) / align * align
+    }
+}
+
+/// Picks the smallest integer type holding `bits` bits that fits in
+/// `max_size` bytes, to store bitfields which can't use their declared type
+/// at the offset clang gives them.
+fn bitfield_bytes_ty(signed: bool, bits: usize, max_size: usize) -> Option<Type> {
+    [1, 2, 4, 8]
+        .iter()
+        .find(|&&size| size * 8 >= bits && size <= max_size)
+        .map(|&size| {
+            let kind = match (signed, size) {
+                (true, 1) => ISChar,
+                (false, 1) => IUChar,
+                (true, 2) => IShort,
+                (false, 2) => IUShort,
+                (true, 4) => IInt,
+                (false, 4) => IUInt,
+                (true, _) => ILongLong,
+                (false, _) => IULongLong,
+            };
+            TInt(kind, Layout::new(size, size))
+        })
+}
+
 /// Converts a C struct to Rust AST Items.
+#[cfg_attr(feature = "clippy", allow(too_many_arguments))]
 fn cstruct_to_rs(ctx: &mut GenCtx,
                  name: &str,
                  options: &BindgenOptions,
                  derive_debug: bool,
                  layout: Layout,
-                 members: Vec<CompMember>)
+                 non_trivial: bool,
+                 members: Vec<CompMember>,
+                 comment: &str)
                  -> Vec<P<ast::Item>> {
     let mut fields: Vec<ast::StructField> = vec![];
     let mut methods = vec![];
@@ -652,10 +1067,18 @@ fn cstruct_to_rs(ctx: &mut GenCtx,
     // Waiting for https://github.com/rust-lang/rfcs/issues/1038
     let mut can_derive_debug = derive_debug;
     let mut can_derive_clone = true;
+    // The atomics and the C++ classes with a non-trivial destructor or copy
+    // constructor are neither `Copy` nor `Clone`.
+    let mut can_derive_copy = !non_trivial;
+
+    // The alignment of the Rust struct, as implied by the emitted fields.
+    let mut rust_align = layout.explicit_align.unwrap_or(1);
 
-    let mut largest_member_alignment = 1;
+    // The offsets in bytes clang computed for the named fields and the
+    // storage of the bitfields.
+    let mut field_offsets = vec![];
 
-    for m in &members {
+    for (i, m) in members.iter().enumerate() {
         debug!("convert field {} {:?}; offset {}", m.name(), m, offset);
 
         let (opt_rc_c, opt_rc_e, opt_f) = match *m {
@@ -666,20 +1089,112 @@ fn cstruct_to_rs(ctx: &mut GenCtx,
             CompMember::EnumField(ref rc_e, ref f) => (None, Some(rc_e), Some(f)),
         };
 
-        if largest_member_alignment < m.layout().align { largest_member_alignment = m.layout().align; }
-
-        if !layout.packed && m.layout().align != 0 && (offset % m.layout().align) != 0 {
-            let padding_size = m.layout().align - (offset % m.layout().align);
-
-            if padding_size > mem::size_of::<u64>() {
-                let mut padding_fields = gen_padding_fields(ctx, paddings, padding_size);
-
-                fields.append(&mut padding_fields);
+        // Named nested declarations don't take any room in the struct.
+        let is_field = match *m {
+            CompMember::Comp(ref rc_c) => rc_c.borrow().name.is_empty(),
+            CompMember::Enum(_) => false,
+            _ => true,
+        };
+        let mut m_align = if !is_field {
+            1
+        } else {
+            let align = m.layout().align;
+            layout.pack.map_or(align, |p| cmp::min(p, align))
+        };
+        let mut m_size = if is_field {
+            m.layout().size
+        } else {
+            0
+        };
+        // The type holding the bitfields, when their declared type can't be
+        // put at the offset clang gives them.
+        let mut bitfield_bytes = None;
+
+        let natural_offset = align_to(offset, m_align);
+        let clang_offset = opt_f.and_then(|f| f.offset);
+        let target = match clang_offset {
+            Some(bits) => {
+                if bits % 8 != 0 {
+                    let msg = format!("The field {}::{} does not start on a byte boundary.",
+                                      name,
+                                      m.name());
+                    ctx.logger.warn(&msg[..]);
+                }
+                bits / 8
+            }
+            None => natural_offset,
+        };
 
-                paddings += padding_fields.len();
+        if let Some(&FieldInfo { bitfields: Some(ref bits), ref ty, .. }) = opt_f {
+            // Bitfields may share their storage unit with the next member.
+            let limit = members[i + 1..]
+                            .iter()
+                            .filter_map(|next| {
+                                match *next {
+                                    CompMember::Field(ref f) |
+                                    CompMember::CompField(_, ref f) |
+                                    CompMember::EnumField(_, ref f) => f.offset.map(|o| o / 8),
+                                    _ => None,
+                                }
+                            })
+                            .next()
+                            .unwrap_or(layout.size);
+            let fits = target == natural_offset && target + m_size <= limit;
+            if !fits && clang_offset.is_some() {
+                let width = bits.iter()
+                                .map(|b| (b.offset + b.width) as usize)
+                                .max()
+                                .unwrap_or(0);
+                let signed = match *ty {
+                    TInt(k, _) => Some(k.is_signed()),
+                    _ => None,
+                };
+                match signed.and_then(|signed| {
+                    bitfield_bytes_ty(signed, width, limit.saturating_sub(target))
+                }) {
+                    Some(bytes_ty) => {
+                        m_size = bytes_ty.size();
+                        m_align = 1;
+                        bitfield_bytes = Some(bytes_ty);
+                    }
+                    None => {
+                        let msg = format!("Can't place the bitfields of {} at their offset {}.",
+                                          name,
+                                          target);
+                        ctx.logger.warn(&msg[..]);
+                    }
+                }
             }
+        }
 
-            offset += padding_size;
+        let natural_offset = align_to(offset, m_align);
+        // Small natural paddings are left to the Rust compiler, the others are
+        // explicit as the alignment of the Rust type may be lower.
+        if target > offset &&
+           (target != natural_offset || target - offset > mem::size_of::<u64>()) {
+            let mut padding_fields = gen_padding_fields(ctx,
+                                                        paddings,
+                                                        offset,
+                                                        target - offset,
+                                                        layout);
+            paddings += padding_fields.len();
+            fields.append(&mut padding_fields);
+        } else if target < offset {
+            let msg = format!("The field {}::{} overlaps the previous one, its offset will be \
+                               wrong.",
+                              name,
+                              m.name());
+            ctx.logger.warn(&msg[..]);
+        } else if target < natural_offset {
+            let msg = format!("The field {}::{} is less aligned than its type, its offset will \
+                               be wrong.",
+                              name,
+                              m.name());
+            ctx.logger.warn(&msg[..]);
+        }
+        offset = cmp::max(offset, target);
+        if m_align > rust_align {
+            rust_align = m_align;
         }
 
         debug!("member {}::{} @ {}, {:?}",
@@ -697,12 +1212,45 @@ fn cstruct_to_rs(ctx: &mut GenCtx,
                 None => rust_id(ctx, &f.name, &options.remove_prefix).0,
             };
 
+            if f.offset.is_some() {
+                field_offsets.push((f_name.clone(), target));
+            }
+
             if !f.ty.can_auto_derive() {
                 can_derive_debug = false;
                 can_derive_clone = false;
             }
+            if !f.ty.is_copy() {
+                can_derive_copy = false;
+            }
+            if options.rust_unions && has_union(&f.ty) {
+                can_derive_debug = false;
+            }
 
-            let f_ty = P(cty_to_rs(ctx, &f.ty, options));
+            let f_ty = P(match (f.bitfields.as_ref(), bitfield_bytes.as_ref()) {
+                (Some(bitfields), Some(bytes_ty)) => {
+                    methods.extend(gen_bitfield_methods(ctx,
+                                                        &f_name,
+                                                        Some(0),
+                                                        bytes_ty,
+                                                        bitfields,
+                                                        options)
+                                       .into_iter());
+                    let u8_ty = mk_ty(ctx, false, vec!["u8".to_owned()]);
+                    mk_arrty(ctx, &u8_ty, bytes_ty.size())
+                }
+                (Some(bitfields), None) => {
+                    methods.extend(gen_bitfield_methods(ctx,
+                                                        &f_name,
+                                                        None,
+                                                        &f.ty,
+                                                        bitfields,
+                                                        options)
+                                       .into_iter());
+                    bitfield_storage_ty(ctx, &f.ty, options)
+                }
+                (None, _) => cty_to_rs(ctx, &f.ty, options),
+            });
 
             fields.push(ast::StructField {
                 span: ctx.span,
@@ -710,13 +1258,14 @@ fn cstruct_to_rs(ctx: &mut GenCtx,
                 ident: Some(ctx.ext_cx.ident_of(&f_name[..])),
                 id: ast::DUMMY_NODE_ID,
                 ty: f_ty,
-                attrs: Vec::new(),
+                attrs: mk_doc_attrs(ctx, &f.comment, options),
             });
         }
 
         if let Some(rc_c) = opt_rc_c {
-            let c = rc_c.borrow();
-            if c.name.is_empty() {
+            let is_anonymous = rc_c.borrow().name.is_empty();
+            if is_anonymous && !options.rust_unions {
+                let c = rc_c.borrow();
                 unnamed += 1;
                 let field_name = format!("_bindgen_data_{}_", unnamed);
                 fields.push(mk_blob_field(ctx, &field_name, c.layout, ctx.span));
@@ -730,13 +1279,37 @@ fn cstruct_to_rs(ctx: &mut GenCtx,
                                                 derive_debug)
                                    .into_iter());
             } else {
+                if is_anonymous {
+                    // Native unions can hold the anonymous member directly,
+                    // without any accessor.
+                    unnamed += 1;
+                    let field_name = format!("_bindgen_data_{}_", unnamed);
+                    let comp_ty = TComp(rc_c.clone());
+                    if has_union(&comp_ty) {
+                        can_derive_debug = false;
+                    }
+                    if !comp_ty.is_copy() {
+                        can_derive_copy = false;
+                    }
+                    fields.push(ast::StructField {
+                        span: ctx.span,
+                        vis: ast::Visibility::Public,
+                        ident: Some(ctx.ext_cx.ident_of(&field_name[..])),
+                        id: ast::DUMMY_NODE_ID,
+                        ty: P(cty_to_rs(ctx, &comp_ty, options)),
+                        attrs: Vec::new(),
+                    });
+                }
+                let c = rc_c.borrow();
                 extra.extend(comp_to_rs(ctx,
                                         c.kind,
                                         comp_name(c.kind, &c.name),
                                         options,
                                         derive_debug,
                                         c.layout,
-                                        c.members.clone())
+                                        c.non_trivial,
+                                        c.members.clone(),
+                                        &c.comment)
                                  .into_iter());
             }
         }
@@ -750,29 +1323,44 @@ fn cstruct_to_rs(ctx: &mut GenCtx,
                                      &enum_name(&e.name),
                                      e.kind,
                                      e.layout,
-                                     &e.items));
+                                     &e.items,
+                                     e.effective_style(options.rust_enums),
+                                     &e.comment));
         }
 
-        offset += m.layout().size as usize;
+        offset += m_size;
     }
 
-    if offset < layout.size {
-        // We only need to pad if the pad amount is more than the existing alignment
-        if layout.size - offset > largest_member_alignment {
-            let mut padding_fields = gen_padding_fields(ctx, paddings, layout.size - offset);
-            fields.append(&mut padding_fields);
-        }
+    // We only need to pad if the Rust alignment doesn't already do it.
+    if offset < layout.size && align_to(offset, rust_align) != layout.size {
+        let mut padding_fields = gen_padding_fields(ctx,
+                                                    paddings,
+                                                    offset,
+                                                    layout.size - offset,
+                                                    layout);
+        fields.append(&mut padding_fields);
     }
 
     let def = ast::ItemKind::Struct(ast::VariantData::Struct(fields, ast::DUMMY_NODE_ID),
                                     ast::Generics::default());
 
-    let id = rust_id(ctx, name, &options.remove_prefix).0;
-    let mut attrs = vec![mk_repr_attr(ctx, layout)];
-    if can_derive_clone {
-        attrs.push(mk_attr(ctx, "derive", &["Copy", "Clone"]));
+    // The fields may be less aligned than the C struct, like the byte arrays
+    // holding misplaced bitfields.
+    let layout = if rust_align < layout.align && layout.pack.is_none() {
+        Layout { explicit_align: Some(layout.align), ..layout }
     } else {
-        attrs.push(mk_attr(ctx, "derive", &["Copy"]));
+        layout
+    };
+
+    let id = rust_id(ctx, name, &options.remove_prefix).0;
+    let mut attrs = mk_doc_attrs(ctx, comment, options);
+    attrs.push(mk_repr_attr(ctx, layout));
+    if can_derive_copy {
+        if can_derive_clone {
+            attrs.push(mk_attr(ctx, "derive", &["Copy", "Clone"]));
+        } else {
+            attrs.push(mk_attr(ctx, "derive", &["Copy"]));
+        }
     }
     if can_derive_debug {
         attrs.push(mk_deriving_debug_attr(ctx));
@@ -803,12 +1391,18 @@ fn cstruct_to_rs(ctx: &mut GenCtx,
             span: ctx.span,
         }));
     }
+    if let Some(impl_) = flex_array_methods_to_rs(ctx, name, &members, options) {
+        items.push(impl_);
+    }
 
-    if !can_derive_clone {
+    if can_derive_copy && !can_derive_clone {
         items.push(mk_clone_impl(ctx, &id, options.use_core));
     }
 
     items.push(mk_default_impl(ctx, &id, options.use_core));
+    if options.layout_tests {
+        items.push(mk_layout_test(ctx, &id, layout, &field_offsets, options.use_core));
+    }
     items.extend(extra.into_iter());
     items
 }
@@ -837,6 +1431,60 @@ fn mk_clone_impl(ctx: &GenCtx, ty_name: &str, use_core: bool) -> P<ast::Item> {
         .unwrap()
 }
 
+/// Converts a type to a struct with only the size and the alignment of the C
+/// type, which can be passed around but whose content is hidden. The blobs of
+/// the non-trivial C++ classes are neither `Copy` nor `Clone`.
+fn opaque_blob_to_rs(ctx: &mut GenCtx,
+                     name: &str,
+                     layout: Layout,
+                     non_trivial: bool,
+                     comment: &str,
+                     options: &BindgenOptions)
+                     -> Vec<P<ast::Item>> {
+    let id = rust_id(ctx, name, &options.remove_prefix).0;
+    // The blob is never misaligned, only the alignment is worth keeping. The
+    // integer blobs are at most 8-aligned.
+    let explicit_align = if layout.align > 8 {
+        Some(layout.align)
+    } else {
+        layout.explicit_align
+    };
+    let layout = Layout {
+        pack: None,
+        explicit_align: explicit_align,
+        ..layout
+    };
+    let blob = mk_blob_field(ctx, "_bindgen_opaque_blob", layout, ctx.span);
+    let blob = ast::StructField { vis: ast::Visibility::Inherited, ..blob };
+    let def = ast::ItemKind::Struct(ast::VariantData::Struct(vec![blob], ast::DUMMY_NODE_ID),
+                                    ast::Generics::default());
+
+    let mut attrs = mk_doc_attrs(ctx, comment, options);
+    attrs.push(mk_repr_attr(ctx, layout));
+    if !non_trivial {
+        attrs.push(mk_attr(ctx, "derive", &["Copy"]));
+    }
+    let struct_def = P(ast::Item {
+        ident: ctx.ext_cx.ident_of(&id),
+        attrs: attrs,
+        id: ast::DUMMY_NODE_ID,
+        node: def,
+        vis: ast::Visibility::Public,
+        span: ctx.span,
+    });
+
+    let mut items = vec![struct_def];
+    if !non_trivial {
+        items.push(mk_clone_impl(ctx, &id, options.use_core));
+    }
+    items.push(mk_default_impl(ctx, &id, options.use_core));
+    if options.layout_tests {
+        let field_offsets = [("_bindgen_opaque_blob".to_owned(), 0)];
+        items.push(mk_layout_test(ctx, &id, layout, &field_offsets, options.use_core));
+    }
+    items
+}
+
 /// Convert a opaque type name to an ast Item.
 fn opaque_to_rs(ctx: &mut GenCtx, name: &str, remove_prefix: &str) -> P<ast::Item> {
     let def = ast::ItemKind::Enum(ast::EnumDef { variants: vec![] }, ast::Generics::default());
@@ -857,7 +1505,8 @@ fn cunion_to_rs(ctx: &mut GenCtx,
                 options: &BindgenOptions,
                 derive_debug: bool,
                 layout: Layout,
-                members: Vec<CompMember>)
+                members: Vec<CompMember>,
+                comment: &str)
                 -> Vec<P<ast::Item>> {
     fn mk_item(ctx: &mut GenCtx,
                name: String,
@@ -875,6 +1524,19 @@ fn cunion_to_rs(ctx: &mut GenCtx,
         })
     }
 
+    let can_be_native = members.iter().all(|member| {
+        match *member {
+            CompMember::Field(ref f) |
+            CompMember::CompField(_, ref f) |
+            CompMember::EnumField(_, ref f) => f.bitfields.is_none() && f.ty.is_copy(),
+            CompMember::Comp(ref rc_c) => TComp(rc_c.clone()).is_copy(),
+            CompMember::Enum(_) => true,
+        }
+    });
+    if options.rust_unions && can_be_native {
+        return rust_union_to_rs(ctx, &name, options, derive_debug, layout, &members, comment);
+    }
+
     let ci = Rc::new(RefCell::new(CompInfo::new(name.clone(),
                                                 CompKind::Union,
                                                 members.clone(),
@@ -903,7 +1565,8 @@ fn cunion_to_rs(ctx: &mut GenCtx,
                                      }
                                  });
     let union_attrs = {
-        let mut attrs = vec![mk_repr_attr(ctx, layout)];
+        let mut attrs = mk_doc_attrs(ctx, comment, options);
+        attrs.push(mk_repr_attr(ctx, layout));
         if can_auto_derive {
             attrs.push(mk_deriving_copy_clone_attr(ctx));
             if derive_debug {
@@ -947,51 +1610,404 @@ fn cunion_to_rs(ctx: &mut GenCtx,
     }
 
     items.push(mk_default_impl(ctx, &union_id, options.use_core));
+    if options.layout_tests {
+        let field_offsets = [(data_field_name.to_owned(), 0)];
+        items.push(mk_layout_test(ctx, &union_id, layout, &field_offsets, options.use_core));
+    }
     items.extend(extra.into_iter());
     items
 }
 
-fn i64_abs(i: i64) -> u64 {
-    if i<0 {
-        i.wrapping_neg() as u64
-    } else {
-        i as u64
-    }
-}
-
-/// Converts a signed number to AST Expression.
-fn i64_to_int_lit(ctx: &mut GenCtx, value: i64) -> P<ast::Expr> {
-    let int_lit = ast::LitKind::Int(i64_abs(value), ast::LitIntType::Unsuffixed);
-    let expr = ctx.ext_cx.expr_lit(ctx.span, int_lit);
-    if value < 0 {
-        let negated = ast::ExprKind::Unary(ast::UnOp::Neg, expr);
-        ctx.ext_cx.expr(ctx.span, negated)
-    } else {
-        expr
-    }
-}
-
-/// Converts a C const to Rust AST.
-fn const_to_rs(ctx: &mut GenCtx,
-               name: &str,
-               val: i64,
-               val_ty: ast::Ty,
-               options: &BindgenOptions)
-               -> P<ast::Item> {
-    let int_lit = i64_to_int_lit(ctx, val);
+/// Converts a C union to a native Rust union, with a typed field for each
+/// member.  Anonymous members get a generated name and type.
+fn rust_union_to_rs(ctx: &mut GenCtx,
+                    name: &str,
+                    options: &BindgenOptions,
+                    derive_debug: bool,
+                    layout: Layout,
+                    members: &[CompMember],
+                    comment: &str)
+                    -> Vec<P<ast::Item>> {
+    let mut fields = vec![];
+    // Every field of a union starts at its beginning.
+    let mut field_offsets = vec![];
+    // Nested composites end up in 'extra' and are emitted after the union.
+    let mut extra = vec![];
+    let mut unnamed: u32 = 0;
 
-    let cst = ast::ItemKind::Const(P(val_ty), int_lit);
+    for m in members {
+        let (field, nested_ty) = match *m {
+            CompMember::Field(ref f) => (Some(f.clone()), None),
+            CompMember::CompField(ref rc_c, ref f) => (Some(f.clone()), Some(TComp(rc_c.clone()))),
+            CompMember::EnumField(ref rc_e, ref f) => (Some(f.clone()), Some(TEnum(rc_e.clone()))),
+            CompMember::Comp(ref rc_c) => {
+                let is_anonymous = rc_c.borrow().name.is_empty();
+                let field = if is_anonymous {
+                    unnamed += 1;
+                    Some(FieldInfo::new(format!("_bindgen_data_{}_", unnamed),
+                                        TComp(rc_c.clone()),
+                                        None))
+                } else {
+                    None
+                };
+                (field, Some(TComp(rc_c.clone())))
+            }
+            CompMember::Enum(ref rc_e) => (None, Some(TEnum(rc_e.clone()))),
+        };
 
-    let id = rust_id(ctx, name, &options.remove_prefix).0;
-    P(ast::Item {
-        ident: ctx.ext_cx.ident_of(&id),
-        attrs: Vec::new(),
-        id: ast::DUMMY_NODE_ID,
-        node: cst,
-        vis: ast::Visibility::Public,
-        span: ctx.span,
-    })
-}
+        if let Some(f) = field {
+            let f_name = rust_id(ctx, &f.name, &options.remove_prefix).0;
+            let f_ty = P(cty_to_rs(ctx, &f.ty, options));
+            field_offsets.push((f_name.clone(), 0));
+            fields.push(ast::StructField {
+                span: ctx.span,
+                vis: ast::Visibility::Public,
+                ident: Some(ctx.ext_cx.ident_of(&f_name[..])),
+                id: ast::DUMMY_NODE_ID,
+                ty: f_ty,
+                attrs: mk_doc_attrs(ctx, &f.comment, options),
+            });
+        }
+
+        match nested_ty {
+            Some(TComp(ref rc_c)) => {
+                let c = rc_c.borrow();
+                extra.extend(comp_to_rs(ctx,
+                                        c.kind,
+                                        comp_name(c.kind, &c.name),
+                                        options,
+                                        derive_debug,
+                                        c.layout,
+                                        c.non_trivial,
+                                        c.members.clone(),
+                                        &c.comment)
+                                 .into_iter());
+            }
+            Some(TEnum(ref rc_e)) => {
+                let e = rc_e.borrow();
+                extra.extend(cenum_to_rs(ctx,
+                                         options,
+                                         derive_debug,
+                                         &enum_name(&e.name),
+                                         e.kind,
+                                         e.layout,
+                                         &e.items,
+                                         e.effective_style(options.rust_enums),
+                                         &e.comment));
+            }
+            _ => {}
+        }
+    }
+
+    let def = ast::ItemKind::Struct(ast::VariantData::Struct(fields, ast::DUMMY_NODE_ID),
+                                    ast::Generics::default());
+    let id = rust_id(ctx, name, &options.remove_prefix).0;
+
+    // Unions can't derive Debug.
+    let mut attrs = mk_doc_attrs(ctx, comment, options);
+    attrs.push(mk_repr_attr(ctx, layout));
+    attrs.push(mk_deriving_copy_clone_attr(ctx));
+    attrs.push(mk_union_marker_attr(ctx));
+    let union_def = P(ast::Item {
+        ident: ctx.ext_cx.ident_of(&id),
+        attrs: attrs,
+        id: ast::DUMMY_NODE_ID,
+        node: def,
+        vis: ast::Visibility::Public,
+        span: ctx.span,
+    });
+
+    let mut items = vec![union_def];
+    items.push(mk_default_impl(ctx, &id, options.use_core));
+    if options.layout_tests {
+        items.push(mk_layout_test(ctx, &id, layout, &field_offsets, options.use_core));
+    }
+    items.extend(extra.into_iter());
+    items
+}
+
+/// Whether a value of the type contains a union, which prevents deriving
+/// `Debug` once unions are emitted as native Rust unions.
+fn has_union(ty: &Type) -> bool {
+    match *ty {
+        TArray(ref t, _, _) => has_union(t),
+        TNamed(ref ti) => has_union(&ti.borrow().ty),
+        TComp(ref ci) => {
+            let c = ci.borrow();
+            c.kind == CompKind::Union ||
+            c.members.iter().any(|member| {
+                match *member {
+                    CompMember::Field(ref f) |
+                    CompMember::CompField(_, ref f) => has_union(&f.ty),
+                    CompMember::Comp(ref rc_c) => {
+                        rc_c.borrow().name.is_empty() && has_union(&TComp(rc_c.clone()))
+                    }
+                    _ => false,
+                }
+            })
+        }
+        _ => false,
+    }
+}
+
+fn i64_abs(i: i64) -> u64 {
+    if i<0 {
+        i.wrapping_neg() as u64
+    } else {
+        i as u64
+    }
+}
+
+/// Converts a signed number to AST Expression.
+fn i64_to_int_lit(ctx: &mut GenCtx, value: i64) -> P<ast::Expr> {
+    let int_lit = ast::LitKind::Int(i64_abs(value), ast::LitIntType::Unsuffixed);
+    let expr = ctx.ext_cx.expr_lit(ctx.span, int_lit);
+    if value < 0 {
+        let negated = ast::ExprKind::Unary(ast::UnOp::Neg, expr);
+        ctx.ext_cx.expr(ctx.span, negated)
+    } else {
+        expr
+    }
+}
+
+fn f64_to_float_lit(ctx: &mut GenCtx, value: f64) -> P<ast::Expr> {
+    // `Debug` always prints a decimal point or an exponent.
+    let digits = parse::token::intern_and_get_ident(&format!("{:?}", value.abs()));
+    let float_lit = ast::LitKind::FloatUnsuffixed(digits);
+    let expr = ctx.ext_cx.expr_lit(ctx.span, float_lit);
+    if value < 0.0 {
+        let negated = ast::ExprKind::Unary(ast::UnOp::Neg, expr);
+        ctx.ext_cx.expr(ctx.span, negated)
+    } else {
+        expr
+    }
+}
+
+/// Converts a C const to Rust AST.
+///
+/// The strings are nul-terminated byte strings, ready for
+/// `CStr::from_bytes_with_nul`, unless `Builder::macro_strings_as_str` is set
+/// and they are valid UTF-8.
+fn const_to_rs(ctx: &mut GenCtx,
+               name: &str,
+               val: &ConstVal,
+               val_ty: ast::Ty,
+               comment: &str,
+               options: &BindgenOptions)
+               -> P<ast::Item> {
+    let (val_ty, lit) = match *val {
+        ConstVal::Int(i) => (val_ty, i64_to_int_lit(ctx, i)),
+        ConstVal::Float(f) => (val_ty, f64_to_float_lit(ctx, f)),
+        ConstVal::Char(c) => (val_ty, ctx.ext_cx.expr_lit(ctx.span, ast::LitKind::Byte(c))),
+        ConstVal::Str(ref bytes) => {
+            let lifetime = ctx.ext_cx.lifetime(ctx.span, parse::token::intern("'static"));
+            match String::from_utf8(bytes.clone()) {
+                Ok(ref s) if options.macro_strings_as_str => {
+                    let str_ty = mk_ty(ctx, false, vec!["str".to_owned()]);
+                    let ty = ctx.ext_cx.ty_rptr(ctx.span,
+                                                P(str_ty),
+                                                Some(lifetime),
+                                                ast::Mutability::Immutable);
+                    let lit = ctx.ext_cx.expr_str(ctx.span, parse::token::intern_and_get_ident(s));
+                    (ty.unwrap(), lit)
+                }
+                _ => {
+                    let mut bytes = bytes.clone();
+                    bytes.push(0);
+                    let u8_ty = mk_ty(ctx, false, vec!["u8".to_owned()]);
+                    let arr_ty = mk_arrty(ctx, &u8_ty, bytes.len());
+                    let ty = ctx.ext_cx.ty_rptr(ctx.span,
+                                                P(arr_ty),
+                                                Some(lifetime),
+                                                ast::Mutability::Immutable);
+                    let lit = ast::LitKind::ByteStr(Rc::new(bytes));
+                    (ty.unwrap(), ctx.ext_cx.expr_lit(ctx.span, lit))
+                }
+            }
+        }
+    };
+
+    let cst = ast::ItemKind::Const(P(val_ty), lit);
+
+    let id = rust_id(ctx, name, &options.remove_prefix).0;
+    P(ast::Item {
+        ident: ctx.ext_cx.ident_of(&id),
+        attrs: mk_doc_attrs(ctx, comment, options),
+        id: ast::DUMMY_NODE_ID,
+        node: cst,
+        vis: ast::Visibility::Public,
+        span: ctx.span,
+    })
+}
+
+/// Converts a group of macros to a newtype with associated constants, which
+/// can be combined with `|`, `&` and `!`.
+fn macro_group_to_rs(ctx: &mut GenCtx,
+                     ti: &TypeInfo,
+                     items: &[(String, i64)],
+                     options: &BindgenOptions)
+                     -> Vec<P<ast::Item>> {
+    let name = rust_id(ctx, &ti.name, &options.remove_prefix).0;
+    let inner = cty_to_rs(ctx, &ti.ty, options);
+    let inner = tts_to_string(&inner.to_tokens(&ctx.ext_cx)[..]);
+    let consts: Vec<_> = items.iter()
+                              .map(|&(ref item, val)| (item.clone(), val.to_string(), String::new()))
+                              .collect();
+    newtype_to_rs(ctx,
+                  options,
+                  options.derive_debug && ti.ty.can_auto_derive(),
+                  &name,
+                  &inner,
+                  &consts,
+                  true,
+                  "")
+}
+
+/// Generates a `#[repr(transparent)]` newtype of `inner` with associated
+/// constants, given as their name, value and comment, and with `bitwise` the
+/// `|`, `&` and `!` operators.
+#[cfg_attr(feature = "clippy", allow(too_many_arguments))]
+fn newtype_to_rs(ctx: &mut GenCtx,
+                 options: &BindgenOptions,
+                 derive_debug: bool,
+                 name: &str,
+                 inner: &str,
+                 consts: &[(String, String, String)],
+                 bitwise: bool,
+                 comment: &str)
+                 -> Vec<P<ast::Item>> {
+    let root_crate = if options.use_core {
+        "core"
+    } else {
+        "std"
+    };
+    let derives = if derive_debug {
+        "Copy, Clone, Debug, PartialEq, Eq, Hash"
+    } else {
+        "Copy, Clone, PartialEq, Eq, Hash"
+    };
+
+    let mut consts_str = String::new();
+    for &(ref item, ref val, _) in consts {
+        let mut id = rust_id(ctx, item, "").0;
+        if id.starts_with(|c: char| c.is_digit(10)) {
+            id = format!("_{}", id);
+        }
+        consts_str.push_str(&format!("    pub const {}: {} = {}({});\n", id, name, name, val));
+    }
+    let mut items_str = format!("#[repr(transparent)]\n#[derive({})]\npub struct {}(pub {});\n\
+                                 impl {} {{\n{}}}\n",
+                                derives,
+                                name,
+                                inner,
+                                name,
+                                consts_str);
+    if bitwise {
+        for &(op_trait, op_fn, op) in &[("BitOr", "bitor", "|"), ("BitAnd", "bitand", "&")] {
+            items_str.push_str(&format!("impl ::{root}::ops::{tr} for {name} {{\n    \
+                                             type Output = Self;\n    \
+                                             #[inline]\n    \
+                                             fn {f}(self, other: Self) -> Self {{\n        \
+                                                 {name}(self.0 {op} other.0)\n    \
+                                             }}\n\
+                                         }}\n",
+                                        root = root_crate,
+                                        tr = op_trait,
+                                        f = op_fn,
+                                        op = op,
+                                        name = name));
+        }
+        items_str.push_str(&format!("impl ::{root}::ops::Not for {name} {{\n    \
+                                         type Output = Self;\n    \
+                                         #[inline]\n    \
+                                         fn not(self) -> Self {{\n        \
+                                             {name}(!self.0)\n    \
+                                         }}\n\
+                                     }}\n",
+                                    root = root_crate,
+                                    name = name));
+    }
+
+    let mut parser = parse::new_parser_from_source_str(ctx.ext_cx.parse_sess(),
+                                                       ctx.ext_cx.cfg(),
+                                                       "".to_owned(),
+                                                       items_str);
+    let mut items = vec![];
+    while let Some(item) = parser.parse_item().unwrap() {
+        items.push(item);
+    }
+
+    // The documentation comments are attached once parsed, so that they need
+    // no escaping.
+    let struct_doc = mk_doc_attrs(ctx, comment, options);
+    let const_docs: Vec<_> = consts.iter()
+                                   .map(|&(_, _, ref comment)| mk_doc_attrs(ctx, comment, options))
+                                   .collect();
+    items[0] = items[0].clone().map(|mut item| {
+        let mut attrs = struct_doc;
+        attrs.extend(item.attrs);
+        item.attrs = attrs;
+        item
+    });
+    items[1] = items[1].clone().map(|mut item| {
+        if let ast::ItemKind::Impl(_, _, _, _, _, ref mut impl_items) = item.node {
+            for (impl_item, doc) in impl_items.iter_mut().zip(const_docs) {
+                impl_item.attrs = doc;
+            }
+        }
+        item
+    });
+    items
+}
+
+/// Converts a function-like macro to a `macro_rules!` macro or to an inline
+/// function on `i64`, see `Builder::fn_macros`.
+fn fn_macro_to_rs(ctx: &mut GenCtx, mi: &MacroInfo, options: &BindgenOptions) -> P<ast::Item> {
+    let is_fn = options.fn_macros == FnMacroStyle::InlineFn;
+    let params: Vec<String> = mi.params.iter().map(|p| rust_id(ctx, p, "").0).collect();
+    let mut body = vec![];
+    for token in &mi.body {
+        body.push(match *token {
+            MacroToken::Param(i) if is_fn => params[i].clone(),
+            MacroToken::Param(i) => format!("${}", params[i]),
+            MacroToken::Const(ref name) => {
+                let id = rust_id(ctx, name, &options.remove_prefix).0;
+                if is_fn {
+                    format!("({} as i64)", id)
+                } else {
+                    id
+                }
+            }
+            MacroToken::Int(i) if i < 0 => format!("({})", i),
+            MacroToken::Int(i) => i.to_string(),
+            MacroToken::Float(f) => format!("{:?}", f),
+            MacroToken::Punct(ref p) => p.clone(),
+        });
+    }
+
+    let name = rust_id(ctx, &mi.name, &options.remove_prefix).0;
+    let item_str = if is_fn {
+        let params: Vec<_> = params.iter().map(|p| format!("{}: i64", p)).collect();
+        format!("#[inline]\npub fn {}({}) -> i64 {{\n    {}\n}}\n",
+                name,
+                params.join(", "),
+                body.join(" "))
+    } else {
+        let params: Vec<_> = params.iter().map(|p| format!("${}:expr", p)).collect();
+        format!("#[macro_export]\nmacro_rules! {} {{\n    ({}) => {{ {} }};\n}}\n",
+                name,
+                params.join(", "),
+                body.join(" "))
+    };
+
+    parse::new_parser_from_source_str(ctx.ext_cx.parse_sess(),
+                                      ctx.ext_cx.cfg(),
+                                      "".to_owned(),
+                                      item_str)
+        .parse_item()
+        .unwrap()
+        .unwrap()
+}
 
 fn enum_size_to_rust_type_name(signed: bool, size: usize) -> &'static str {
     match (signed, size) {
@@ -1032,35 +2048,184 @@ fn cenum_value_to_int_lit(ctx: &mut GenCtx,
     }
 }
 
+/// The names of the variants of an enum: given by
+/// `ParseCallbacks::enum_variant_name`, or else without the longest prefix
+/// they all share up to an underscore with `strip_enum_prefix`, as long as
+/// they remain valid identifiers, and in UpperCamelCase with
+/// `camel_case_enum_variants`. The prefix is only stripped from the variants
+/// scoped by their enum, the global constants of two enums could collide
+/// otherwise.
+fn enum_variant_names(enum_name: &str,
+                      enum_items: &[EnumItem],
+                      style: EnumStyle,
+                      options: &BindgenOptions)
+                      -> Vec<String> {
+    let mut prefix_len = 0;
+    if options.strip_enum_prefix && style != EnumStyle::Consts && enum_items.len() > 1 {
+        let first = &enum_items[0].name;
+        let mut common = first.len();
+        for item in &enum_items[1..] {
+            common = first.bytes()
+                          .zip(item.name.bytes())
+                          .take(common)
+                          .take_while(|&(a, b)| a == b)
+                          .count();
+        }
+        // Only strip whole words, and keep at least one character that can
+        // start an identifier.
+        let mut candidates: Vec<usize> = first.bytes()
+                                              .take(common)
+                                              .enumerate()
+                                              .filter(|&(_, b)| b == b'_')
+                                              .map(|(i, _)| i + 1)
+                                              .collect();
+        candidates.reverse();
+        prefix_len = candidates.into_iter()
+                               .find(|&len| {
+                                   enum_items.iter().all(|item| {
+                                       item.name[len..]
+                                           .chars()
+                                           .next()
+                                           .map_or(false, |c| c.is_alphabetic() || c == '_')
+                                   })
+                               })
+                               .unwrap_or(0);
+    }
+
+    enum_items.iter()
+              .map(|item| {
+                  let callbacks = options.parse_callbacks.as_ref();
+                  if let Some(name) = callbacks.and_then(|callbacks| {
+                      callbacks.enum_variant_name(enum_name, &item.name)
+                  }) {
+                      return name;
+                  }
+                  let name = &item.name[prefix_len..];
+                  if options.camel_case_enum_variants {
+                      upper_camel_case(name)
+                  } else {
+                      name.to_owned()
+                  }
+              })
+              .collect()
+}
+
+/// Converts a `SHOUTING_CASE` or `snake_case` name to UpperCamelCase, keeping
+/// the leading underscores and the case of the words already mixed-case.
+fn upper_camel_case(name: &str) -> String {
+    let trimmed = name.trim_left_matches('_');
+    let mut res: String = name[..name.len() - trimmed.len()].to_owned();
+    for word in trimmed.split('_').filter(|word| !word.is_empty()) {
+        let is_mixed = word.chars().any(|c| c.is_lowercase()) &&
+                       word.chars().any(|c| c.is_uppercase());
+        let mut chars = word.chars();
+        if let Some(c) = chars.next() {
+            res.extend(c.to_uppercase());
+            if is_mixed {
+                res.extend(chars);
+            } else {
+                res.extend(chars.flat_map(|c| c.to_lowercase()));
+            }
+        }
+    }
+    if res.is_empty() {
+        name.to_owned()
+    } else {
+        res
+    }
+}
+
+#[cfg_attr(feature = "clippy", allow(too_many_arguments))]
 fn cenum_to_rs(ctx: &mut GenCtx,
                options: &BindgenOptions,
                derive_debug: bool,
                name: &str,
                kind: IKind,
                layout: Layout,
-               enum_items: &[EnumItem])
+               enum_items: &[EnumItem],
+               style: EnumStyle,
+               comment: &str)
                -> Vec<P<ast::Item>> {
     let mangled_name = rust_id(ctx, name, &options.remove_prefix).0;
     let enum_name = ctx.ext_cx.ident_of(&mangled_name);
     let enum_ty = ctx.ext_cx.ty_ident(ctx.span, enum_name);
     let enum_is_signed = kind.is_signed();
     let enum_repr = enum_size_to_rust_type_name(enum_is_signed, layout.size);
+    let variant_names = enum_variant_names(&mangled_name, enum_items, style, options);
     let mut items = vec![];
 
-    if !options.rust_enums {
+    match style {
+        EnumStyle::Newtype | EnumStyle::Bitfield => {
+            let consts: Vec<_> = enum_items.iter()
+                                           .zip(&variant_names)
+                                           .map(|(item, variant)| {
+                                               let value = if enum_is_signed {
+                                                   item.val.to_string()
+                                               } else {
+                                                   let max = enum_size_to_unsigned_max_value(layout.size);
+                                                   (item.val as u64 & max).to_string()
+                                               };
+                                               (rust_id(ctx, variant, &options.remove_prefix).0,
+                                                value,
+                                                item.comment.clone())
+                                           })
+                                           .collect();
+            return newtype_to_rs(ctx,
+                                 options,
+                                 derive_debug,
+                                 &mangled_name,
+                                 enum_repr,
+                                 &consts,
+                                 style == EnumStyle::Bitfield,
+                                 comment);
+        }
+        EnumStyle::ModuleConsts => {
+            let type_name = ctx.ext_cx.ident_of("Type");
+            let repr_ty = ctx.ext_cx.ty_ident(ctx.span, ctx.ext_cx.ident_of(enum_repr));
+            items.push(ctx.ext_cx.item_ty(ctx.span, type_name, repr_ty)
+                       .map(|p|ast::Item{vis:ast::Visibility::Public,..p}));
+            let ty = ctx.ext_cx.ty_ident(ctx.span, type_name);
+            for (item, variant) in enum_items.iter().zip(&variant_names) {
+                let rust_name = rust_id(ctx, variant, &options.remove_prefix).0;
+                let value = cenum_value_to_int_lit(ctx, enum_is_signed, layout.size, item.val);
+                let item_attrs = mk_doc_attrs(ctx, &item.comment, options);
+                items.push(ctx.ext_cx.item_const(ctx.span,
+                                                 ctx.ext_cx.ident_of(&rust_name),
+                                                 ty.clone(),
+                                                 value)
+                           .map(|p|ast::Item{vis:ast::Visibility::Public,attrs:item_attrs,..p}));
+            }
+            return vec![P(ast::Item {
+                ident: enum_name,
+                attrs: mk_doc_attrs(ctx, comment, options),
+                id: ast::DUMMY_NODE_ID,
+                node: ast::ItemKind::Mod(ast::Mod {
+                    inner: ctx.span,
+                    items: items,
+                }),
+                vis: ast::Visibility::Public,
+                span: ctx.span,
+            })];
+        }
+        EnumStyle::Consts | EnumStyle::Rust => {}
+    }
+
+    if style == EnumStyle::Consts {
+        let ty_attrs = mk_doc_attrs(ctx, comment, options);
         items.push(ctx.ext_cx.item_ty(ctx.span,
                                       enum_name,
                                       ctx.ext_cx
                                          .ty_ident(ctx.span, ctx.ext_cx.ident_of(enum_repr)))
-                   .map(|p|ast::Item{vis:ast::Visibility::Public,..p}));
-        for item in enum_items {
-            let rust_name = rust_id(ctx, &item.name, &options.remove_prefix).0;
+                   .map(|p|ast::Item{vis:ast::Visibility::Public,attrs:ty_attrs,..p}));
+        for (item, variant) in enum_items.iter().zip(&variant_names) {
+            let rust_name = rust_id(ctx, variant, &options.remove_prefix).0;
             let value = cenum_value_to_int_lit(ctx, enum_is_signed, layout.size, item.val);
+            let item_attrs = mk_doc_attrs(ctx, &item.comment, options);
             items.push(ctx.ext_cx.item_const(ctx.span,
                                              ctx.ext_cx.ident_of(&rust_name),
                                              enum_ty.clone(),
                                              value)
-                       .map(|p|ast::Item{vis:ast::Visibility::Public,..p}));
+                       .map(|p|ast::Item{vis:ast::Visibility::Public,attrs:item_attrs,..p}));
         }
         return items;
     }
@@ -1068,8 +2233,8 @@ fn cenum_to_rs(ctx: &mut GenCtx,
     let mut variants = vec![];
     let mut found_values = HashMap::new();
 
-    for item in enum_items {
-        let rust_name = rust_id(ctx, &item.name, &options.remove_prefix).0;
+    for (item, variant) in enum_items.iter().zip(&variant_names) {
+        let rust_name = rust_id(ctx, variant, &options.remove_prefix).0;
         let name = ctx.ext_cx.ident_of(&rust_name);
 
         if let Some(orig) = found_values.get(&item.val) {
@@ -1077,7 +2242,7 @@ fn cenum_to_rs(ctx: &mut GenCtx,
             // Can't use ctx.ext_cx.item because of Visibility::Public
             items.push(P(ast::Item {
                 ident: name,
-                attrs: vec![],
+                attrs: mk_doc_attrs(ctx, &item.comment, options),
                 id: ast::DUMMY_NODE_ID,
                 node: ast::ItemKind::Const(enum_ty.clone(), value),
                 vis: ast::Visibility::Public,
@@ -1093,7 +2258,7 @@ fn cenum_to_rs(ctx: &mut GenCtx,
         variants.push(respan(ctx.span,
                              ast::Variant_ {
                                  name: name,
-                                 attrs: vec![],
+                                 attrs: mk_doc_attrs(ctx, &item.comment, options),
                                  data: ast::VariantData::Unit(ast::DUMMY_NODE_ID),
                                  disr_expr: Some(value),
                              }));
@@ -1102,7 +2267,9 @@ fn cenum_to_rs(ctx: &mut GenCtx,
     let repr_attr = mk_attr(ctx, "repr", &[enum_repr]);
 
     let attrs = {
-        let mut v = vec![mk_deriving_copy_clone_attr(ctx), repr_attr];
+        let mut v = mk_doc_attrs(ctx, comment, options);
+        v.push(mk_deriving_copy_clone_attr(ctx));
+        v.push(repr_attr);
         if derive_debug {
             v.push(mk_deriving_debug_attr(ctx));
         }
@@ -1138,11 +2305,6 @@ fn gen_comp_methods(ctx: &mut GenCtx,
                     -> Vec<ast::ImplItem> {
 
     let mk_field_method = |ctx: &mut GenCtx, f: &FieldInfo, offset: usize| {
-        // TODO: Implement bitfield accessors
-        if f.bitfields.is_some() {
-            return None;
-        }
-
         let (f_name, _) = rust_id(ctx, &f.name, &options.remove_prefix);
         let ret_ty = P(cty_to_rs(ctx,
                                  &TPtr(Box::new(f.ty.clone()), false, Layout::default()),
@@ -1191,7 +2353,17 @@ fn gen_comp_methods(ctx: &mut GenCtx,
     for m in members.into_iter() {
         let advance_by = match *m {
             CompMember::Field(ref f) => {
-                methods.extend(mk_field_method(ctx, f, offset).into_iter());
+                if let Some(ref bitfields) = f.bitfields {
+                    methods.extend(gen_bitfield_methods(ctx,
+                                                        data_field,
+                                                        Some(offset),
+                                                        &f.ty,
+                                                        bitfields,
+                                                        options)
+                                       .into_iter());
+                } else {
+                    methods.extend(mk_field_method(ctx, f, offset).into_iter());
+                }
                 f.ty.size()
             }
             CompMember::Comp(ref rc_c) => {
@@ -1217,12 +2389,25 @@ fn gen_comp_methods(ctx: &mut GenCtx,
                                         options,
                                         derive_debug,
                                         c.layout,
-                                        c.members.clone())
+                                        c.non_trivial,
+                                        c.members.clone(),
+                                        &c.comment)
                                  .into_iter());
                 f.ty.size()
             }
             CompMember::Enum(ref rc_e) => rc_e.borrow().layout.size,
-            CompMember::EnumField(ref _rc_e, ref f) => f.ty.size(),
+            CompMember::EnumField(ref _rc_e, ref f) => {
+                if let Some(ref bitfields) = f.bitfields {
+                    methods.extend(gen_bitfield_methods(ctx,
+                                                        data_field,
+                                                        Some(offset),
+                                                        &f.ty,
+                                                        bitfields,
+                                                        options)
+                                       .into_iter());
+                }
+                f.ty.size()
+            }
         };
         match kind {
             CompKind::Struct => {
@@ -1234,6 +2419,150 @@ fn gen_comp_methods(ctx: &mut GenCtx,
     methods
 }
 
+/// Returns the integer type used to store a bitfield.  C allows enums in
+/// bitfields, but a Rust enum can not be shifted or masked, so those are
+/// stored as the underlying integer of the enum.
+fn bitfield_storage_ty(ctx: &mut GenCtx, ty: &Type, options: &BindgenOptions) -> ast::Ty {
+    match *ty {
+        TEnum(ref ei) => {
+            let e = ei.borrow();
+            let int_ty = enum_size_to_rust_type_name(e.kind.is_signed(), e.layout.size);
+            mk_ty(ctx, false, vec![int_ty.to_owned()])
+        }
+        _ => cty_to_rs(ctx, ty, options),
+    }
+}
+
+/// Generates a getter and a setter for every named bitfield packed in
+/// `storage_field`.  When `blob_offset` is set, the storage lives at that byte
+/// offset inside the untyped array `storage_field`.  The `storage` value may
+/// be smaller than the declared types of the bitfields.
+fn gen_bitfield_methods(ctx: &mut GenCtx,
+                        storage_field: &str,
+                        blob_offset: Option<usize>,
+                        storage: &Type,
+                        bitfields: &[Bitfield],
+                        options: &BindgenOptions)
+                        -> Vec<ast::ImplItem> {
+    let root_crate = if options.use_core {
+        "core"
+    } else {
+        "std"
+    };
+
+    let storage_ty = bitfield_storage_ty(ctx, storage, options);
+    let storage_ty = tts_to_string(&storage_ty.to_tokens(&ctx.ext_cx)[..]);
+    let storage_bits = storage.size() * 8;
+
+    let (load, store) = match blob_offset {
+        None => {
+            (format!("self.{}", storage_field),
+             format!("self.{}", storage_field))
+        }
+        Some(offset) => {
+            (format!("*(::{}::mem::transmute::<_, *const u8>(&self.{}).offset({}) as *const {})",
+                     root_crate,
+                     storage_field,
+                     offset,
+                     storage_ty),
+             format!("*(::{}::mem::transmute::<_, *mut u8>(&mut self.{}).offset({}) as *mut {})",
+                     root_crate,
+                     storage_field,
+                     offset,
+                     storage_ty))
+        }
+    };
+
+    let mut impl_str = String::from("impl X {");
+    for bitfield in bitfields {
+        let (offset, width) = (bitfield.offset as usize, bitfield.width as usize);
+
+        // Unnamed bitfields are only padding.
+        if bitfield.name.is_empty() || width == 0 {
+            continue;
+        }
+
+        let ty = &bitfield.ty;
+        let field_ty = cty_to_rs(ctx, ty, options);
+        let field_ty = tts_to_string(&field_ty.to_tokens(&ctx.ext_cx)[..]);
+        let is_enum = match *ty {
+            TEnum(_) => true,
+            _ => false,
+        };
+
+        let ones = if width >= 64 {
+            !0u64
+        } else {
+            (1u64 << width) - 1
+        };
+        let mask = ones << offset;
+
+        // Shifting left then right sign-extends signed bitfields.
+        let mut value = format!("({} << {}) >> {}",
+                                load,
+                                storage_bits - offset - width,
+                                storage_bits - width);
+        if is_enum {
+            if storage.size() != ty.size() {
+                let int_ty = bitfield_storage_ty(ctx, ty, options);
+                value = format!("({}) as {}",
+                                value,
+                                tts_to_string(&int_ty.to_tokens(&ctx.ext_cx)[..]));
+            }
+            value = format!("::{}::mem::transmute({})", root_crate, value);
+        } else if storage != ty {
+            value = format!("({}) as {}", value, field_ty);
+        }
+        if is_enum || blob_offset.is_some() {
+            value = format!("unsafe {{ {} }}", value);
+        }
+
+        let mut update = format!("{} = ({} & !mask) | (((val as {}) << {}) & mask);",
+                                 store,
+                                 load,
+                                 storage_ty,
+                                 offset);
+        if blob_offset.is_some() {
+            update = format!("unsafe {{ {} }}", update);
+        }
+
+        let name = rust_id(ctx, &bitfield.name, &options.remove_prefix).0;
+        impl_str.push_str(&format!(r"
+            #[inline]
+            pub fn {name}(&self) -> {ty} {{
+                {value}
+            }}
+            #[inline]
+            pub fn set_{name}(&mut self, val: {ty}) {{
+                let mask = {mask}u64 as {storage_ty};
+                {update}
+            }}
+        ",
+                                   name = name,
+                                   ty = field_ty,
+                                   value = value,
+                                   mask = mask,
+                                   storage_ty = storage_ty,
+                                   update = update));
+    }
+    impl_str.push_str("}");
+
+    let item = parse::new_parser_from_source_str(ctx.ext_cx.parse_sess(),
+                                                 ctx.ext_cx.cfg(),
+                                                 "".to_owned(),
+                                                 impl_str)
+                   .parse_item()
+                   .unwrap()
+                   .unwrap();
+
+    item.and_then(|i| {
+        match i.node {
+            ast::ItemKind::Impl(_, _, _, _, _, items) => items,
+            _ => unreachable!("impl parsed to something other than impl"),
+        }
+    })
+}
+
 // Implements std::default::Default using std::mem::zeroed.
 fn mk_default_impl(ctx: &GenCtx, ty_name: &str, use_core: bool) -> P<ast::Item> {
     let root_crate = if use_core {
@@ -1258,7 +2587,74 @@ fn mk_default_impl(ctx: &GenCtx, ty_name: &str, use_core: bool) -> P<ast::Item>
         .unwrap()
 }
 
+/// Generates a test checking the size and the alignment of a type, and the
+/// offsets of its fields, against the numbers given by clang.
+fn mk_layout_test(ctx: &GenCtx,
+                  ty_name: &str,
+                  layout: Layout,
+                  field_offsets: &[(String, usize)],
+                  use_core: bool)
+                  -> P<ast::Item> {
+    let root_crate = if use_core {
+        "core"
+    } else {
+        "std"
+    };
+    let mut checks = format!(r"
+            assert_eq!(::{root_crate}::mem::size_of::<{ty}>(), {size}usize);
+            assert_eq!(::{root_crate}::mem::align_of::<{ty}>(), {align}usize);",
+                             root_crate = root_crate,
+                             ty = ty_name,
+                             size = layout.size,
+                             align = layout.align);
+    // The offsets are measured on a real value, as taking the address of a
+    // field through a null pointer is undefined behaviour.
+    if !field_offsets.is_empty() {
+        checks.push_str(&format!(r"
+            let v: {ty} = ::{root_crate}::default::Default::default();",
+                                 root_crate = root_crate,
+                                 ty = ty_name));
+    }
+    for &(ref field, offset) in field_offsets {
+        checks.push_str(&format!(r"
+            assert_eq!(::{root_crate}::ptr::addr_of!(v.{field}) as usize -
+                       &v as *const {ty} as usize,
+                       {offset}usize);",
+                                 root_crate = root_crate,
+                                 ty = ty_name,
+                                 field = field,
+                                 offset = offset));
+    }
+    let test_str = format!(r"
+        #[test]
+        fn bindgen_test_layout_{}() {{{}
+        }}
+    ",
+                           ty_name,
+                           checks);
+
+    parse::new_parser_from_source_str(ctx.ext_cx.parse_sess(),
+                                      ctx.ext_cx.cfg(),
+                                      "".to_owned(),
+                                      test_str)
+        .parse_item()
+        .unwrap()
+        .unwrap()
+}
+
 fn mk_blob_field(ctx: &GenCtx, name: &str, layout: Layout, span: Span) -> ast::StructField {
+    ast::StructField {
+        span: span,
+        vis: ast::Visibility::Public,
+        ident: Some(ctx.ext_cx.ident_of(name)),
+        id: ast::DUMMY_NODE_ID,
+        ty: P(mk_blob_ty(ctx, layout)),
+        attrs: Vec::new(),
+    }
+}
+
+/// An array with the size and the alignment of the layout.
+fn mk_blob_ty(ctx: &GenCtx, layout: Layout) -> ast::Ty {
     let ty_name = match layout.align {
         8 => "u64",
         4 => "u32",
@@ -1271,15 +2667,7 @@ fn mk_blob_field(ctx: &GenCtx, name: &str, layout: Layout, span: Span) -> ast::S
         layout.size / layout.align
     };
     let base_ty = mk_ty(ctx, false, vec![ty_name.to_owned()]);
-    let data_ty = P(mk_arrty(ctx, &base_ty, data_len));
-    ast::StructField {
-        span: span,
-        vis: ast::Visibility::Public,
-        ident: Some(ctx.ext_cx.ident_of(name)),
-        id: ast::DUMMY_NODE_ID,
-        ty: data_ty,
-        attrs: Vec::new(),
-    }
+    mk_arrty(ctx, &base_ty, data_len)
 }
 
 fn mk_link_name_attr(ctx: &mut GenCtx, name: &str) -> ast::Attribute {
@@ -1297,11 +2685,118 @@ fn mk_link_name_attr(ctx: &mut GenCtx, name: &str) -> ast::Attribute {
            })
 }
 
+/// Converts a raw C comment into one `///` documentation attribute per line.
+fn mk_doc_attrs(ctx: &mut GenCtx,
+                comment: &str,
+                options: &BindgenOptions)
+                -> Vec<ast::Attribute> {
+    if !options.generate_comments {
+        return vec![];
+    }
+
+    comment_lines(comment, options.strip_doxygen)
+        .iter()
+        .map(|line| {
+            let attr = {
+                let k = ctx.ext_cx.name_of("doc").as_str();
+                let v = ctx.ext_cx.name_of(&format!("///{}", line)).as_str();
+                ctx.ext_cx.meta_name_value(ctx.span, k, ast::LitKind::Str(v, ast::StrStyle::Cooked))
+            };
+            respan(ctx.span,
+                   ast::Attribute_ {
+                       id: mk_attr_id(),
+                       style: ast::AttrStyle::Outer,
+                       value: attr,
+                       is_sugared_doc: true,
+                   })
+        })
+        .collect()
+}
+
+/// Strips the comment markers from a raw C comment. Every returned line keeps
+/// its leading space, if any, so that it can follow a `///` directly. The
+/// Doxygen parameters become a Markdown list when `strip_doxygen` is set.
+fn comment_lines(comment: &str, strip_doxygen: bool) -> Vec<String> {
+    let mut lines = vec![];
+    let mut params = vec![];
+    for line in comment.lines() {
+        let mut line = line.trim();
+        for marker in &["/**<", "///<", "//!<", "/**", "/*!", "///", "//!", "/*", "//"] {
+            if line.starts_with(marker) {
+                line = &line[marker.len()..];
+                break;
+            }
+        }
+        if line.ends_with("*/") {
+            line = &line[..line.len() - 2];
+        }
+        if line.starts_with('*') {
+            line = &line[1..];
+        }
+        let mut line = line.trim_right();
+
+        if strip_doxygen {
+            let cmd = line.trim_left();
+            if cmd.starts_with('\\') || cmd.starts_with('@') {
+                let (name, rest) = cmd[1..].split_at(cmd[1..].find(' ').unwrap_or(cmd.len() - 1));
+                match name {
+                    "brief" | "details" => line = rest,
+                    _ if name == "param" || name == "arg" || name.starts_with("param[") => {
+                        params.push(param_line(rest));
+                        continue;
+                    }
+                    _ => continue,
+                }
+            }
+        }
+
+        if line.is_empty() || line.starts_with(' ') {
+            lines.push(line.to_owned());
+        } else {
+            lines.push(format!(" {}", line));
+        }
+    }
+
+    while lines.last().map_or(false, |l| l.trim().is_empty()) {
+        lines.pop();
+    }
+    let leading = lines.iter().take_while(|l| l.trim().is_empty()).count();
+    lines.drain(..leading);
+    if !params.is_empty() {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(" # Parameters".to_owned());
+        lines.push(String::new());
+        lines.extend(params.into_iter());
+    }
+    lines
+}
+
+/// Formats the rest of a Doxygen `\param` command, like `p The point.`, as an
+/// item of a Markdown list.
+fn param_line(rest: &str) -> String {
+    let rest = rest.trim();
+    let (name, desc) = rest.split_at(rest.find(char::is_whitespace).unwrap_or(rest.len()));
+    let desc = desc.trim();
+    if desc.is_empty() {
+        format!(" * `{}`", name)
+    } else {
+        format!(" * `{}` - {}", name, desc)
+    }
+}
+
 fn mk_repr_attr(ctx: &mut GenCtx, layout: Layout) -> ast::Attribute {
-    let mut values = vec!["C"];
-    if layout.packed {
-        values.push("packed");
+    let mut values = vec!["C".to_owned()];
+    match layout.pack {
+        Some(1) => values.push("packed".to_owned()),
+        Some(n) => values.push(format!("packed({})", n)),
+        None => {}
+    }
+    if let Some(n) = layout.explicit_align {
+        values.push(format!("align({})", n));
     }
+    let values: Vec<_> = values.iter().map(|v| &v[..]).collect();
     mk_attr(ctx, "repr", &values)
 }
 
@@ -1314,6 +2809,20 @@ fn mk_deriving_debug_attr(ctx: &mut GenCtx) -> ast::Attribute {
 }
 
 
+/// Marks a struct standing for a native Rust union, which the AST can't
+/// represent. `Bindings::write` prints the marked structs as unions.
+fn mk_union_marker_attr(ctx: &mut GenCtx) -> ast::Attribute {
+    let word = ctx.ext_cx.name_of(UNION_MARKER).as_str();
+    let attr = ctx.ext_cx.meta_word(ctx.span, word);
+    respan(ctx.span,
+           ast::Attribute_ {
+               id: mk_attr_id(),
+               style: ast::AttrStyle::Outer,
+               value: attr,
+               is_sugared_doc: false,
+           })
+}
+
 fn mk_attr(ctx: &mut GenCtx, name: &str, args: &[&str]) -> ast::Attribute {
     mk_attr_style(ctx, name, args, ast::AttrStyle::Outer)
 }
@@ -1346,11 +2855,15 @@ fn cvar_to_rs(ctx: &mut GenCtx,
               name: String,
               ty: &Type,
               is_const: bool,
+              comment: &str,
               options: &BindgenOptions)
               -> ast::ForeignItem {
-    let (rust_name, was_mangled) = rust_id(ctx, &name, &options.remove_prefix);
+    let (rust_name, was_mangled) = match callback_item_name(options, &name, ItemKind::Var) {
+        Some(new_name) => (rust_id(ctx, &new_name, "").0, true),
+        None => rust_id(ctx, &name, &options.remove_prefix),
+    };
 
-    let mut attrs = Vec::new();
+    let mut attrs = mk_doc_attrs(ctx, comment, options);
     if was_mangled {
         attrs.push(mk_link_name_attr(ctx, &name));
     }
@@ -1426,11 +2939,14 @@ fn cfuncty_to_rs(ctx: &mut GenCtx,
     }
 }
 
+#[cfg_attr(feature = "clippy", allow(too_many_arguments))]
 fn cfunc_to_rs(ctx: &mut GenCtx,
                name: String,
+               link_name: Option<String>,
                rty: &Type,
                aty: &[(String, Type)],
                var: bool,
+               comment: &str,
                options: &BindgenOptions)
                -> ast::ForeignItem {
     let var = !aty.is_empty() && var;
@@ -1439,14 +2955,162 @@ fn cfunc_to_rs(ctx: &mut GenCtx,
 
     let (rust_name, was_mangled) = rust_id(ctx, &name, &options.remove_prefix);
 
-    let mut attrs = Vec::new();
-    if was_mangled {
+    let mut attrs = mk_doc_attrs(ctx, comment, options);
+    if let Some(link_name) = link_name {
+        attrs.push(mk_link_name_attr(ctx, &link_name));
+    } else if was_mangled {
         attrs.push(mk_link_name_attr(ctx, &name));
     }
 
     mk_foreign_item(ctx, &rust_name, attrs, decl)
 }
 
+/// Generates the extern declarations of the methods of a C++ class, named
+/// `Class_method` and linked through the mangled names, and the inherent impl
+/// calling them.
+fn cxx_methods_to_rs(ctx: &mut GenCtx,
+                     ci: &Rc<RefCell<CompInfo>>,
+                     options: &BindgenOptions)
+                     -> Vec<P<ast::Item>> {
+    let (class_name, methods) = {
+        let c = ci.borrow();
+        (comp_name(c.kind, &c.name), c.methods.clone())
+    };
+    if methods.is_empty() {
+        return vec![];
+    }
+    let class_name = rust_id(ctx, &class_name, &options.remove_prefix).0;
+    let root_crate = if options.use_core {
+        "core"
+    } else {
+        "std"
+    };
+
+    let mut decls = HashMap::new();
+    let mut wrappers = String::new();
+    let mut overloads: HashMap<String, usize> = HashMap::new();
+    for m in &methods {
+        if passes_non_trivial_class(&m.sig) {
+            let msg = format!("Skipping the method `{}::{}`, which passes a C++ class with a \
+                               non-trivial destructor or copy constructor by value.",
+                              class_name,
+                              m.name);
+            ctx.logger.warn(&msg[..]);
+            continue;
+        }
+        let base_name = match m.kind {
+            MethodKind::Constructor => "new".to_owned(),
+            MethodKind::Destructor => "destruct".to_owned(),
+            MethodKind::Normal | MethodKind::Static => rust_id(ctx, &m.name, "").0,
+        };
+        // Overloads are told apart by a numeric suffix.
+        let name = {
+            let count = overloads.entry(base_name.clone()).or_insert(0);
+            *count += 1;
+            if *count == 1 {
+                base_name
+            } else {
+                format!("{}{}", base_name, *count - 1)
+            }
+        };
+        let extern_name = format!("{}_{}", class_name, name);
+
+        let mut args = vec![];
+        if m.kind != MethodKind::Static {
+            let this_ty = TPtr(Box::new(TComp(ci.clone())), m.is_const, Layout::default());
+            args.push(("this".to_owned(), this_ty));
+        }
+        args.extend(m.sig.args.iter().cloned());
+        let decl = cfunc_to_rs(ctx,
+                               extern_name.clone(),
+                               Some(m.mangled_name.clone()),
+                               &*m.sig.ret_ty,
+                               &args[..],
+                               m.sig.is_variadic,
+                               &m.comment,
+                               options);
+        decls.entry(m.sig.abi).or_insert_with(Vec::new).push(decl);
+
+        // The variadic arguments can't be forwarded.
+        if m.sig.is_variadic {
+            continue;
+        }
+        let mut params = vec![];
+        let mut call_args = vec![];
+        match m.kind {
+            MethodKind::Normal if m.is_const => {
+                params.push("&self".to_owned());
+                call_args.push("self".to_owned());
+            }
+            MethodKind::Normal | MethodKind::Destructor => {
+                params.push("&mut self".to_owned());
+                call_args.push("self".to_owned());
+            }
+            MethodKind::Constructor => call_args.push("__bindgen_tmp.as_mut_ptr()".to_owned()),
+            MethodKind::Static => {}
+        }
+        for (i, &(ref n, ref t)) in m.sig.args.iter().enumerate() {
+            let arg_name = if n.is_empty() {
+                format!("arg{}", i + 1)
+            } else {
+                rust_id(ctx, n, &options.remove_prefix).0
+            };
+            let arg_ty = match *t {
+                TArray(ref typ, _, l) => cty_to_rs(ctx, &TPtr(typ.clone(), false, l), options),
+                _ => cty_to_rs(ctx, t, options),
+            };
+            params.push(format!("{}: {}", arg_name, tts_to_string(&arg_ty.to_tokens(&ctx.ext_cx)[..])));
+            call_args.push(arg_name);
+        }
+        let call = format!("{}({})", extern_name, call_args.join(", "));
+
+        if options.generate_comments {
+            for line in comment_lines(&m.comment, options.strip_doxygen) {
+                wrappers.push_str(&format!("///{}\n", line));
+            }
+        }
+        let wrapper = match m.kind {
+            MethodKind::Constructor => {
+                format!("#[inline]\npub unsafe fn {}({}) -> Self {{\n    let mut __bindgen_tmp = \
+                         ::{}::mem::MaybeUninit::<Self>::uninit();\n    {};\n    \
+                         __bindgen_tmp.assume_init()\n}}\n",
+                        name,
+                        params.join(", "),
+                        root_crate,
+                        call)
+            }
+            _ => {
+                let ret = match *m.sig.ret_ty {
+                    TVoid => String::new(),
+                    ref t => {
+                        let ty = cty_to_rs(ctx, t, options);
+                        format!(" -> {}", tts_to_string(&ty.to_tokens(&ctx.ext_cx)[..]))
+                    }
+                };
+                format!("#[inline]\npub unsafe fn {}({}){} {{\n    {}\n}}\n",
+                        name,
+                        params.join(", "),
+                        ret,
+                        call)
+            }
+        };
+        wrappers.push_str(&wrapper);
+    }
+
+    let mut items: Vec<_> = decls.into_iter()
+                                 .map(|(abi, decls)| mk_extern(ctx, &options.links, decls, abi))
+                                 .collect();
+    let impl_str = format!("impl {} {{\n{}}}\n", class_name, wrappers);
+    items.push(parse::new_parser_from_source_str(ctx.ext_cx.parse_sess(),
+                                                 ctx.ext_cx.cfg(),
+                                                 "".to_owned(),
+                                                 impl_str)
+                   .parse_item()
+                   .unwrap()
+                   .unwrap());
+    items
+}
+
 fn is_named_fnproto(ty: &Type) -> bool {
     if let TNamed(ref rc) = *ty {
         if let TFuncProto(..) = rc.borrow().ty {
@@ -1487,9 +3151,11 @@ fn cty_to_rs(ctx: &mut GenCtx, ty: &Type, options: &BindgenOptions) -> ast::Ty {
                 ILongLong => mk_ty(ctx, true, raw("c_longlong")),
                 IULongLong => mk_ty(ctx, true, raw("c_ulonglong")),
                 IWChar => mk_ty(ctx, true, raw("wchar_t")),
+                I128 => mk_ty(ctx, false, vec!["i128".to_owned()]),
+                IU128 => mk_ty(ctx, false, vec!["u128".to_owned()]),
             }
         }
-        TFloat(f, _) => {
+        TFloat(f, ref layout) => {
             match f {
                 FFloat => {
                     mk_ty(ctx,
@@ -1509,8 +3175,55 @@ fn cty_to_rs(ctx: &mut GenCtx, ty: &Type, options: &BindgenOptions) -> ast::Ty {
                               raw("c_double")
                           })
                 }
+                FLongDouble | FFloat128 => {
+                    if !ctx.float_blobs.iter().any(|&(kind, _)| kind == f) {
+                        ctx.float_blobs.push((f, *layout));
+                    }
+                    let id = rust_id(ctx, float_blob_name(f), &options.remove_prefix).0;
+                    let path = item_path(ctx, &[], id);
+                    mk_ty(ctx, false, path)
+                }
             }
         }
+        TAtomic(ref t, layout) => {
+            let root_crate = if options.use_core {
+                "core"
+            } else {
+                "std"
+            };
+            let mut path = vec![root_crate.to_owned(), "sync".to_owned(), "atomic".to_owned()];
+            match t.atomic_value() {
+                Some(TPtr(ref pointee, _, _)) => {
+                    path.push("AtomicPtr".to_owned());
+                    let pointee = cty_to_rs(ctx, pointee, options);
+                    mk_generic_ty(ctx, true, path, pointee)
+                }
+                Some(TInt(IBool, _)) => {
+                    path.push("AtomicBool".to_owned());
+                    mk_ty(ctx, true, path)
+                }
+                Some(TInt(kind, _)) => {
+                    path.push(format!("Atomic{}{}",
+                                      if kind.is_signed() { "I" } else { "U" },
+                                      layout.size * 8));
+                    mk_ty(ctx, true, path)
+                }
+                _ => {
+                    ctx.logger.warn("An atomic has no Rust equivalent and is made opaque.");
+                    mk_blob_ty(ctx, layout)
+                }
+            }
+        }
+        TComplex(f, ref layout) => {
+            let part = TFloat(f, Layout::new(layout.size / 2, layout.align));
+            let part = cty_to_rs(ctx, &part, options);
+            if !options.complex_type.is_empty() {
+                return mk_generic_ty(ctx, true, options.complex_type.clone(), part);
+            }
+            ctx.complex = true;
+            let path = item_path(ctx, &[], COMPLEX_STRUCT.to_owned());
+            mk_generic_ty(ctx, false, path, part)
+        }
         TPtr(ref t, is_const, _) => {
             let id = cty_to_rs(ctx, &**t, options);
             if is_named_fnproto(&**t) {
@@ -1523,6 +3236,36 @@ fn cty_to_rs(ctx: &mut GenCtx, ty: &Type, options: &BindgenOptions) -> ast::Ty {
             let ty = cty_to_rs(ctx, &**t, options);
             mk_arrty(ctx, &ty, s)
         }
+        TFlexArray(ref t) => {
+            ctx.flex_array = true;
+            let ty = cty_to_rs(ctx, &**t, options);
+            let path = item_path(ctx, &[], FLEX_ARRAY_MARKER.to_owned());
+            mk_generic_ty(ctx, false, path, ty)
+        }
+        TVector(ref t, s, layout) => {
+            if options.arch_vector_types {
+                let arch = arch_module(&options.clang_args);
+                if let (Some(arch), Some(name)) = (arch, arch_vector_name(t, layout.size)) {
+                    let root_crate = if options.use_core {
+                        "core"
+                    } else {
+                        "std"
+                    };
+                    let path = vec![root_crate.to_owned(),
+                                    "arch".to_owned(),
+                                    arch.to_owned(),
+                                    name];
+                    return mk_ty(ctx, true, path);
+                }
+            }
+            if !ctx.vector_aligns.contains(&layout.align) {
+                ctx.vector_aligns.push(layout.align);
+            }
+            let ty = cty_to_rs(ctx, &**t, options);
+            let arr = mk_arrty(ctx, &ty, s);
+            let path = item_path(ctx, &[], vector_wrapper_name(layout.align));
+            mk_generic_ty(ctx, false, path, arr)
+        }
         TFuncPtr(ref sig, _) | TFuncProto(ref sig, _) => {
             let decl = cfuncty_to_rs(ctx, &*sig.ret_ty, &sig.args[..], sig.is_variadic, options);
             let unsafety = if sig.is_safe {
@@ -1533,22 +3276,238 @@ fn cty_to_rs(ctx: &mut GenCtx, ty: &Type, options: &BindgenOptions) -> ast::Ty {
             mk_fnty(ctx, decl, unsafety, sig.abi, options.use_core)
         }
         TNamed(ref ti) => {
-            let id = rust_id(ctx, &ti.borrow().name, &options.remove_prefix).0;
-            mk_ty(ctx, false, vec![id])
+            let ti = ti.borrow();
+            let id = rust_id(ctx, &ti.name, &options.remove_prefix).0;
+            let path = item_path(ctx, &ti.namespace, id);
+            mk_ty(ctx, false, path)
         }
         TComp(ref ci) => {
             let mut c = ci.borrow_mut();
             c.name = unnamed_name(ctx, &c.name);
             c.name = rust_id(ctx, &c.name, &options.remove_prefix).0;
-            mk_ty(ctx, false, vec![comp_name(c.kind, &c.name)])
+            let path = item_path(ctx, &c.namespace, comp_name(c.kind, &c.name));
+            mk_ty(ctx, false, path)
         }
         TEnum(ref ei) => {
             let mut e = ei.borrow_mut();
             e.name = unnamed_name(ctx, &e.name);
             e.name = rust_id(ctx, &e.name, &options.remove_prefix).0;
-            mk_ty(ctx, false, vec![enum_name(&e.name)])
+            let mut path = item_path(ctx, &e.namespace, enum_name(&e.name));
+            if e.effective_style(options.rust_enums) == EnumStyle::ModuleConsts {
+                path.push("Type".to_owned());
+            }
+            mk_ty(ctx, false, path)
+        }
+    }
+}
+
+/// The name of the blob standing for a float without a Rust equivalent.
+fn float_blob_name(kind: FKind) -> &'static str {
+    match kind {
+        FLongDouble => "__BindgenLongDouble",
+        FFloat128 => "__BindgenFloat128",
+        FFloat | FDouble => unreachable!(),
+    }
+}
+
+/// The module of `core::arch` holding the intrinsic types of the target
+/// given to clang, or else of the host, if it is an x86 one.
+fn arch_module(clang_args: &[String]) -> Option<&'static str> {
+    let arch = match clang_target(clang_args) {
+        Some(target) => target.split('-').next().unwrap_or(""),
+        None => std::env::consts::ARCH,
+    };
+    match arch {
+        "x86_64" | "amd64" => Some("x86_64"),
+        "x86" | "i386" | "i486" | "i586" | "i686" => Some("x86"),
+        _ => None,
+    }
+}
+
+/// The name of the `core::arch` type of a vector of the given size, if there
+/// is one for its elements.
+fn arch_vector_name(elem: &Type, size: usize) -> Option<String> {
+    let suffix = match *elem {
+        TFloat(FFloat, _) => "",
+        TFloat(FDouble, _) => "d",
+        TInt(IBool, _) => return None,
+        TInt(..) => "i",
+        TNamed(ref ti) => return arch_vector_name(&ti.borrow().ty, size),
+        _ => return None,
+    };
+    match size {
+        16 | 32 | 64 => Some(format!("__m{}{}", size * 8, suffix)),
+        _ => None,
+    }
+}
+
+/// The name of the wrapper giving its alignment to the array standing for a
+/// vector.
+fn vector_wrapper_name(align: usize) -> String {
+    format!("__BindgenVector{}", align)
+}
+
+/// Defines the wrapper of the vectors of the given alignment.
+fn vector_wrapper_to_rs(ctx: &mut GenCtx, align: usize) -> P<ast::Item> {
+    let item_str = format!("#[repr(C, align({align}))]\n\
+                            #[derive(Copy, Clone, Debug)]\n\
+                            pub struct {name}<T>(pub T);\n",
+                           align = align,
+                           name = vector_wrapper_name(align));
+    parse::new_parser_from_source_str(ctx.ext_cx.parse_sess(),
+                                      ctx.ext_cx.cfg(),
+                                      "".to_owned(),
+                                      item_str)
+        .parse_item()
+        .unwrap()
+        .unwrap()
+}
+
+const COMPLEX_STRUCT: &'static str = "__BindgenComplex";
+
+/// Defines the struct standing for the C complex numbers, unless
+/// `Builder::complex_type` replaces it.
+fn complex_to_rs(ctx: &mut GenCtx) -> P<ast::Item> {
+    let item_str = format!("/// A complex number, with the layout of the C `_Complex` types.\n\
+                            #[repr(C)]\n\
+                            #[derive(Copy, Clone, Debug, Default, PartialEq)]\n\
+                            pub struct {name}<T> {{\n    \
+                                pub re: T,\n    \
+                                pub im: T,\n\
+                            }}\n",
+                           name = COMPLEX_STRUCT);
+    parse::new_parser_from_source_str(ctx.ext_cx.parse_sess(),
+                                      ctx.ext_cx.cfg(),
+                                      "".to_owned(),
+                                      item_str)
+        .parse_item()
+        .unwrap()
+        .unwrap()
+}
+
+const FLEX_ARRAY_MARKER: &'static str = "__BindgenFlexArray";
+
+/// Defines the zero-sized marker of the flexible array members, and its
+/// accessors to the elements following the struct. It is neither `Copy` nor
+/// `Clone`, as a copy of the struct would lose the elements.
+fn flex_array_marker_to_rs(ctx: &mut GenCtx, options: &BindgenOptions) -> Vec<P<ast::Item>> {
+    let items_str = format!("/// A flexible array member, whose elements follow the struct.\n\
+                             #[repr(C)]\n\
+                             pub struct {name}<T>([T; 0]);\n\
+                             impl<T> {name}<T> {{\n    \
+                                 #[inline]\n    \
+                                 pub unsafe fn as_ptr(&self) -> *const T {{\n        \
+                                     self as *const {name}<T> as *const T\n    \
+                                 }}\n    \
+                                 #[inline]\n    \
+                                 pub unsafe fn as_mut_ptr(&mut self) -> *mut T {{\n        \
+                                     self as *mut {name}<T> as *mut T\n    \
+                                 }}\n    \
+                                 #[inline]\n    \
+                                 pub unsafe fn as_slice(&self, len: usize) -> &[T] {{\n        \
+                                     ::{root}::slice::from_raw_parts(self.as_ptr(), len)\n    \
+                                 }}\n    \
+                                 #[inline]\n    \
+                                 pub unsafe fn as_mut_slice(&mut self, len: usize) -> &mut [T] {{\n        \
+                                     ::{root}::slice::from_raw_parts_mut(self.as_mut_ptr(), len)\n    \
+                                 }}\n\
+                             }}\n\
+                             impl<T> ::{root}::fmt::Debug for {name}<T> {{\n    \
+                                 fn fmt(&self, f: &mut ::{root}::fmt::Formatter) -> ::{root}::fmt::Result {{\n        \
+                                     f.write_str(\"{name}\")\n    \
+                                 }}\n\
+                             }}\n",
+                            name = FLEX_ARRAY_MARKER,
+                            root = if options.use_core { "core" } else { "std" });
+    let mut parser = parse::new_parser_from_source_str(ctx.ext_cx.parse_sess(),
+                                                       ctx.ext_cx.cfg(),
+                                                       "".to_owned(),
+                                                       items_str);
+    let mut items = vec![];
+    while let Some(item) = parser.parse_item().unwrap() {
+        items.push(item);
+    }
+    items
+}
+
+/// Generates the methods of a struct returning its flexible array member as
+/// a slice, whose length is the field set with `Builder::flexible_array_len`.
+fn flex_array_methods_to_rs(ctx: &mut GenCtx,
+                            name: &str,
+                            members: &[CompMember],
+                            options: &BindgenOptions)
+                            -> Option<P<ast::Item>> {
+    let len_field = match options.flexible_array_lens.iter().find(|&&(ref s, _)| s == name) {
+        Some(&(_, ref len_field)) => len_field,
+        None => return None,
+    };
+    let (array_field, elem_ty) = match members.last() {
+        Some(&CompMember::Field(FieldInfo { name: ref f_name, ty: TFlexArray(ref t), .. })) => {
+            (f_name.clone(), (**t).clone())
         }
+        _ => {
+            let msg = format!("The struct {} has no flexible array member.", name);
+            ctx.logger.warn(&msg[..]);
+            return None;
+        }
+    };
+    let has_len_field = members.iter().any(|m| {
+        match *m {
+            CompMember::Field(ref f) => f.name == *len_field && f.bitfields.is_none(),
+            _ => false,
+        }
+    });
+    if !has_len_field {
+        let msg = format!("The struct {} has no field {} for the length of its flexible array \
+                           member.",
+                          name,
+                          len_field);
+        ctx.logger.warn(&msg[..]);
+        return None;
     }
+
+    let id = rust_id(ctx, name, &options.remove_prefix).0;
+    let array_field = rust_id(ctx, &array_field, &options.remove_prefix).0;
+    let len_field = rust_id(ctx, len_field, &options.remove_prefix).0;
+    let elem_ty = cty_to_rs(ctx, &elem_ty, options);
+    let elem_ty = tts_to_string(&elem_ty.to_tokens(&ctx.ext_cx)[..]);
+    let impl_str = format!("impl {id} {{\n    \
+                                #[inline]\n    \
+                                pub unsafe fn {f}(&self) -> &[{ty}] {{\n        \
+                                    self.{f}.as_slice(self.{len} as usize)\n    \
+                                }}\n    \
+                                #[inline]\n    \
+                                pub unsafe fn {f}_mut(&mut self) -> &mut [{ty}] {{\n        \
+                                    let len = self.{len} as usize;\n        \
+                                    self.{f}.as_mut_slice(len)\n    \
+                                }}\n\
+                            }}\n",
+                           id = id,
+                           f = array_field,
+                           ty = elem_ty,
+                           len = len_field);
+    parse::new_parser_from_source_str(ctx.ext_cx.parse_sess(),
+                                      ctx.ext_cx.cfg(),
+                                      "".to_owned(),
+                                      impl_str)
+        .parse_item()
+        .unwrap()
+}
+
+/// The path to an item declared in a C++ namespace, relative to the module
+/// being generated.
+fn item_path(ctx: &mut GenCtx, namespace: &[String], name: String) -> Vec<String> {
+    let common = ctx.namespace
+                    .iter()
+                    .zip(namespace)
+                    .take_while(|&(a, b)| a == b)
+                    .count();
+    let mut path: Vec<String> = ctx.namespace[common..].iter().map(|_| "super".to_owned()).collect();
+    for module in &namespace[common..] {
+        path.push(rust_id(ctx, module, "").0);
+    }
+    path.push(name);
+    path
 }
 
 fn mk_ty(ctx: &GenCtx, global: bool, segments: Vec<String>) -> ast::Ty {
@@ -1573,6 +3532,23 @@ fn mk_ty(ctx: &GenCtx, global: bool, segments: Vec<String>) -> ast::Ty {
     ctx.ext_cx.ty(ctx.span, ty).unwrap()
 }
 
+/// Makes the type of the path, with a single type parameter on its last
+/// segment.
+fn mk_generic_ty(ctx: &GenCtx, global: bool, segments: Vec<String>, param: ast::Ty) -> ast::Ty {
+    let mut ty = mk_ty(ctx, global, segments);
+    if let ast::TyKind::Path(_, ref mut path) = ty.node {
+        if let Some(segment) = path.segments.last_mut() {
+            segment.parameters =
+                ast::PathParameters::AngleBracketed(ast::AngleBracketedParameterData {
+                    lifetimes: Vec::new(),
+                    types: P::from_vec(vec![P(param)]),
+                    bindings: P::new(),
+                });
+        }
+    }
+    ty
+}
+
 fn mk_ptrty(ctx: &mut GenCtx, base: ast::Ty, is_const: bool) -> ast::Ty {
     let mutability = if is_const {
         ast::Mutability::Immutable
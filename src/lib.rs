@@ -16,17 +16,20 @@ extern crate libc;
 #[macro_use]
 extern crate log;
 extern crate cexpr;
+extern crate regex;
 
 use std::collections::HashSet;
 use std::default::Default;
 use std::io::{self, Write};
 use std::fs::OpenOptions;
 use std::path::Path;
+use std::rc::Rc;
 
 use syntax::ast;
+use syntax::attr::AttrMetaMethods;
 use syntax::codemap::{DUMMY_SP, Span};
 use syntax::print::pprust;
-use syntax::print::pp::eof;
+use syntax::print::pp::{self, eof};
 use syntax::ptr::P;
 
 use types::Global;
@@ -37,6 +40,8 @@ mod types;
 mod clang;
 mod gen;
 mod parser;
+mod filter;
+mod macro_types;
 
 /// A builder to generate bindings.
 #[derive(Debug, Clone)]
@@ -62,6 +67,55 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Generate bindings for the functions whose name matches the regex, and
+    /// for all the types they depend on.
+    pub fn allowlist_function<T: Into<String>>(&mut self, arg: T) -> &mut Self {
+        self.options.allowlist_functions.push(arg.into());
+        self
+    }
+
+    /// Generate bindings for the types whose name matches the regex, and for
+    /// all the types they depend on.
+    pub fn allowlist_type<T: Into<String>>(&mut self, arg: T) -> &mut Self {
+        self.options.allowlist_types.push(arg.into());
+        self
+    }
+
+    /// Generate bindings for the global variables and constants whose name
+    /// matches the regex, and for all the types they depend on.
+    pub fn allowlist_var<T: Into<String>>(&mut self, arg: T) -> &mut Self {
+        self.options.allowlist_vars.push(arg.into());
+        self
+    }
+
+    /// Never generate bindings for the functions whose name matches the regex.
+    pub fn blocklist_function<T: Into<String>>(&mut self, arg: T) -> &mut Self {
+        self.options.blocklist_functions.push(arg.into());
+        self
+    }
+
+    /// Never generate bindings for the types whose name matches the regex.
+    /// The items referring to them still do, so they must be defined
+    /// elsewhere.
+    pub fn blocklist_type<T: Into<String>>(&mut self, arg: T) -> &mut Self {
+        self.options.blocklist_types.push(arg.into());
+        self
+    }
+
+    /// Never generate bindings for the global variables and constants whose
+    /// name matches the regex.
+    pub fn blocklist_var<T: Into<String>>(&mut self, arg: T) -> &mut Self {
+        self.options.blocklist_vars.push(arg.into());
+        self
+    }
+
+    /// Generate the structs, unions and typedefs whose name matches the regex
+    /// as blobs of the same size and alignment, hiding their fields.
+    pub fn opaque_type<T: Into<String>>(&mut self, arg: T) -> &mut Self {
+        self.options.opaque_types.push(arg.into());
+        self
+    }
+
     /// Add a clang CLI argument.
     pub fn clang_arg<T: Into<String>>(&mut self, arg: T) -> &mut Self {
         self.options.clang_args.push(arg.into());
@@ -98,12 +152,135 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Convert the C enums whose name matches the regex to a newtype of their
+    /// integer type with the variants as associated constants, which unlike a
+    /// Rust enum accepts the values C may pass without declaring them.
+    pub fn newtype_enum<T: Into<String>>(&mut self, regex: T) -> &mut Self {
+        self.options.newtype_enums.push(regex.into());
+        self
+    }
+
+    /// Convert the C enums whose name matches the regex to a module holding a
+    /// `Type` alias and the variants as constants of that type.
+    pub fn constified_enum_module<T: Into<String>>(&mut self, regex: T) -> &mut Self {
+        self.options.constified_enum_modules.push(regex.into());
+        self
+    }
+
+    /// Like `newtype_enum`, with the variants combinable with `|`, `&` and
+    /// `!`, for the C enums whose name matches the regex.
+    pub fn bitfield_enum<T: Into<String>>(&mut self, regex: T) -> &mut Self {
+        self.options.bitfield_enums.push(regex.into());
+        self
+    }
+
+    /// Strip from the enum variants the longest prefix they all share, up to
+    /// an underscore, so that `COLOR_RED` and `COLOR_GREEN` become `RED` and
+    /// `GREEN`. The prefix is kept when a variant would start with a digit,
+    /// and for the enums translated to global constants.
+    pub fn strip_enum_prefix(&mut self, value: bool) -> &mut Self {
+        self.options.strip_enum_prefix = value;
+        self
+    }
+
+    /// Convert the enum variants to UpperCamelCase, `RED_LIGHT` becoming
+    /// `RedLight`.
+    pub fn camel_case_enum_variants(&mut self, value: bool) -> &mut Self {
+        self.options.camel_case_enum_variants = value;
+        self
+    }
+
+    /// Control if bindgen should convert the C unions to native rust unions
+    /// instead of structs with accessors. Requires the `untagged_unions`
+    /// feature.
+    pub fn rust_unions(&mut self, value: bool) -> &mut Self {
+        self.options.rust_unions = value;
+        self
+    }
+
+    /// Control if the C documentation comments are copied into the generated
+    /// code as `///` comments.
+    pub fn generate_comments(&mut self, value: bool) -> &mut Self {
+        self.options.generate_comments = value;
+        self
+    }
+
+    /// Drop the Doxygen commands (`@return`, `\see`, ...) from the copied
+    /// comments, keeping the text of `\brief` and `\details` and turning the
+    /// `\param` ones into a Markdown list of the parameters.
+    pub fn strip_doxygen(&mut self, value: bool) -> &mut Self {
+        self.options.strip_doxygen = value;
+        self
+    }
+
+    /// Control if a `#[test]` checking the size, the alignment and the field
+    /// offsets against the ones computed by clang is generated for each
+    /// struct and union. The offsets are measured with `ptr::addr_of!`, which
+    /// needs Rust 1.51 to build.
+    pub fn layout_tests(&mut self, value: bool) -> &mut Self {
+        self.options.layout_tests = value;
+        self
+    }
+
+    /// Control if the `static` functions, like the `static inline` helpers of
+    /// the headers, are bound. They are called through exported C wrappers,
+    /// see `Bindings::write_static_wrappers_to_file`.
+    pub fn wrap_static_fns(&mut self, value: bool) -> &mut Self {
+        self.options.wrap_static_fns = value;
+        self
+    }
+
     /// Set the logger to use.
     pub fn log(&mut self, logger: &'a Logger) -> &mut Self {
         self.logger = Some(logger);
         self
     }
 
+    /// Convert `__int128` and `unsigned __int128` to `i128` and `u128`
+    /// instead of structs of two 64-bit halves. The Rust types only match the
+    /// C ABI on recent compilers and some targets.
+    pub fn native_int128(&mut self, value: bool) -> &mut Self {
+        self.options.native_int128 = value;
+        self
+    }
+
+    /// Convert the SIMD vectors matching an x86 intrinsic type, like
+    /// `__m128i`, to the `core::arch::x86_64` or `core::arch::x86` one,
+    /// following the clang `-target` or else the host. The other vectors, and
+    /// all of them on the other targets, are arrays in an aligned wrapper.
+    pub fn arch_vector_types(&mut self, value: bool) -> &mut Self {
+        self.options.arch_vector_types = value;
+        self
+    }
+
+    /// Sets the path of the generic type to use for the C complex numbers,
+    /// like `num_complex::Complex`, instead of a generated struct. It must
+    /// have the layout of its real part followed by its imaginary part.
+    pub fn complex_type<T: Into<String>>(&mut self, path: T) -> &mut Self {
+        let path: String = path.into();
+        self.options.complex_type = path.split("::")
+                                        .filter(|segment| !segment.is_empty())
+                                        .map(String::from)
+                                        .collect();
+        self
+    }
+
+    /// Generate for the struct `name` the methods returning its flexible array
+    /// member as a slice, whose length is the field `len_field`.
+    pub fn flexible_array_len<T: Into<String>, U: Into<String>>(&mut self,
+                                                               name: T,
+                                                               len_field: U)
+                                                               -> &mut Self {
+        self.options.flexible_array_lens.push((name.into(), len_field.into()));
+        self
+    }
+
+    /// Set the callbacks choosing the names of the generated items.
+    pub fn parse_callbacks<T: ParseCallbacks + 'static>(&mut self, callbacks: T) -> &mut Self {
+        self.options.parse_callbacks = Some(Rc::new(callbacks));
+        self
+    }
+
     /// Overrides the type used to represent a C enum.
     pub fn override_enum_ty<T: Into<String>>(&mut self, ty: T) -> &mut Self {
         self.options.override_enum_ty = ty.into();
@@ -149,6 +326,64 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Give the integer macros whose name matches the regex the given type,
+    /// either a C integer type spelled as for `override_enum_ty` or an integer
+    /// typedef or enum of the headers. Only used with `convert_macros`.
+    pub fn macro_type<T: Into<String>, U: Into<String>>(&mut self, regex: T, ty: U) -> &mut Self {
+        self.options.macro_type_overrides.push((regex.into(), ty.into()));
+        self
+    }
+
+    /// Give the integer macros the type of the integer typedef or enum whose
+    /// name, minus a `_t` like suffix, prefixes theirs: `FLAGS_READ` gets the
+    /// type `flags_t`. `macro_type` takes precedence. The values which don't
+    /// fit in the type keep the one of their magnitude.
+    ///
+    /// The type is only inferred from the names, not from the values the
+    /// macros are compared with in the headers.
+    pub fn infer_macro_types(&mut self, value: bool) -> &mut Self {
+        self.options.infer_macro_types = value;
+        self
+    }
+
+    /// Collect the integer macros whose name starts with `prefix` into a
+    /// `#[repr(transparent)]` newtype named `name`, with the macros, minus the
+    /// prefix, as associated constants that can be combined with `|`, `&`
+    /// and `!`. Only used with `convert_macros`.
+    pub fn macro_group<T: Into<String>, U: Into<String>>(&mut self, prefix: T, name: U) -> &mut Self {
+        self.options.macro_groups.push((prefix.into(), name.into()));
+        self
+    }
+
+    /// Give the parameter `param` of the function `func`, or the type it
+    /// points to, the type of the macro group `name`. The newtype then wraps
+    /// the C type of the parameter, which all the macros must fit in.
+    pub fn macro_group_param<T, U, V>(&mut self, func: T, param: U, name: V) -> &mut Self
+        where T: Into<String>,
+              U: Into<String>,
+              V: Into<String>
+    {
+        self.options.macro_group_params.push((func.into(), param.into(), name.into()));
+        self
+    }
+
+    /// Convert the function-like macros whose body is an arithmetic
+    /// expression, like `#define MAKE_VERSION(a, b) (((a) << 8) | (b))`, in
+    /// the given style. The other ones are reported with `Logger::warn`.
+    /// Only used with `convert_macros`.
+    pub fn fn_macros(&mut self, style: FnMacroStyle) -> &mut Self {
+        self.options.fn_macros = style;
+        self
+    }
+
+    /// Convert the string macros to `&'static str` instead of nul-terminated
+    /// `&'static [u8; N]`, which `CStr::from_bytes_with_nul` accepts. The
+    /// strings that are not valid UTF-8 stay byte strings.
+    pub fn macro_strings_as_str(&mut self, value: bool) -> &mut Self {
+        self.options.macro_strings_as_str = value;
+        self
+    }
+
     /// When converting macros, convert integers that would fit in a `u8`,
     /// `u16`, `u32`, `u64`, `i8`, `i16`, `i32`, `i64` to the corresponding
     /// named C type the supplied list.
@@ -182,14 +417,58 @@ impl<'a> Builder<'a> {
 #[doc(hidden)]
 pub struct BindgenOptions {
     pub match_pat: Vec<String>,
+    /// See `Builder::allowlist_function`.
+    pub allowlist_functions: Vec<String>,
+    /// See `Builder::allowlist_type`.
+    pub allowlist_types: Vec<String>,
+    /// See `Builder::allowlist_var`.
+    pub allowlist_vars: Vec<String>,
+    /// See `Builder::blocklist_function`.
+    pub blocklist_functions: Vec<String>,
+    /// See `Builder::blocklist_type`.
+    pub blocklist_types: Vec<String>,
+    /// See `Builder::blocklist_var`.
+    pub blocklist_vars: Vec<String>,
+    /// See `Builder::opaque_type`.
+    pub opaque_types: Vec<String>,
     pub builtins: bool,
     pub rust_enums: bool,
+    /// See `Builder::newtype_enum`.
+    pub newtype_enums: Vec<String>,
+    /// See `Builder::constified_enum_module`.
+    pub constified_enum_modules: Vec<String>,
+    /// See `Builder::bitfield_enum`.
+    pub bitfield_enums: Vec<String>,
+    /// See `Builder::strip_enum_prefix`.
+    pub strip_enum_prefix: bool,
+    /// See `Builder::camel_case_enum_variants`.
+    pub camel_case_enum_variants: bool,
+    /// See `Builder::parse_callbacks`.
+    pub parse_callbacks: Option<Rc<ParseCallbacks>>,
+    /// See `Builder::native_int128`.
+    pub native_int128: bool,
+    /// See `Builder::arch_vector_types`.
+    pub arch_vector_types: bool,
+    /// The segments of the path given to `Builder::complex_type`.
+    pub complex_type: Vec<String>,
+    /// See `Builder::flexible_array_len`.
+    pub flexible_array_lens: Vec<(String, String)>,
+    /// See `Builder::rust_unions`.
+    pub rust_unions: bool,
     pub links: Vec<(String, LinkType)>,
     pub emit_ast: bool,
     pub fail_on_unknown_type: bool,
     pub override_enum_ty: String,
     pub clang_args: Vec<String>,
     pub derive_debug: bool,
+    /// See `Builder::wrap_static_fns`.
+    pub wrap_static_fns: bool,
+    /// See `Builder::layout_tests`.
+    pub layout_tests: bool,
+    /// See `Builder::generate_comments`.
+    pub generate_comments: bool,
+    /// See `Builder::strip_doxygen`.
+    pub strip_doxygen: bool,
     /// The prefix to use for the c types like c_void.
     ///
     /// Default: ["std", "os", "raw"]
@@ -201,6 +480,18 @@ pub struct BindgenOptions {
     /// See `Builder::convert_floats`.
     pub convert_floats: bool,
     pub convert_macros: bool,
+    /// See `Builder::macro_strings_as_str`.
+    pub macro_strings_as_str: bool,
+    /// See `Builder::fn_macros`.
+    pub fn_macros: FnMacroStyle,
+    /// See `Builder::macro_type`.
+    pub macro_type_overrides: Vec<(String, String)>,
+    /// See `Builder::infer_macro_types`.
+    pub infer_macro_types: bool,
+    /// See `Builder::macro_group`.
+    pub macro_groups: Vec<(String, String)>,
+    /// See `Builder::macro_group_param`.
+    pub macro_group_params: Vec<(String, String, String)>,
     // would use Array here but that requires Copy
     pub macro_int_types: (String,String,String,String,String,String,String,String),
 }
@@ -218,19 +509,47 @@ impl Default for BindgenOptions {
         }
         BindgenOptions {
             match_pat: Vec::new(),
+            allowlist_functions: Vec::new(),
+            allowlist_types: Vec::new(),
+            allowlist_vars: Vec::new(),
+            blocklist_functions: Vec::new(),
+            blocklist_types: Vec::new(),
+            blocklist_vars: Vec::new(),
+            opaque_types: Vec::new(),
             builtins: false,
             rust_enums: true,
+            newtype_enums: Vec::new(),
+            constified_enum_modules: Vec::new(),
+            bitfield_enums: Vec::new(),
+            strip_enum_prefix: false,
+            camel_case_enum_variants: false,
+            parse_callbacks: None,
+            native_int128: false,
+            arch_vector_types: false,
+            complex_type: vec![],
+            flexible_array_lens: Vec::new(),
+            rust_unions: false,
             links: Vec::new(),
             emit_ast: false,
             fail_on_unknown_type: true,
             override_enum_ty: "".to_owned(),
             clang_args: args,
             derive_debug: true,
+            wrap_static_fns: false,
+            layout_tests: false,
+            generate_comments: true,
+            strip_doxygen: false,
             ctypes_prefix: vec!["std".into(), "os".into(), "raw".into()],
             use_core: false,
             remove_prefix: String::new(),
             convert_floats: true,
             convert_macros: false,
+            macro_strings_as_str: false,
+            fn_macros: FnMacroStyle::Skip,
+            macro_type_overrides: Vec::new(),
+            infer_macro_types: false,
+            macro_groups: Vec::new(),
+            macro_group_params: Vec::new(),
             macro_int_types: ("uchar".to_owned(),"ushort".to_owned(),"uint".to_owned(),"ulonglong".to_owned(),"schar".to_owned(),"sshort".to_owned(),"sint".to_owned(),"slonglong".to_owned())
         }
     }
@@ -247,6 +566,18 @@ pub enum LinkType {
     Framework,
 }
 
+/// How the function-like macros are converted, see `Builder::fn_macros`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FnMacroStyle {
+    /// Skip them.
+    Skip,
+    /// Generate `macro_rules!` macros, generic over the argument types. The
+    /// constants they use are replaced by their values.
+    MacroRules,
+    /// Generate inline functions taking and returning `i64`.
+    InlineFn,
+}
+
 /// Trait used internaly to log things with context like the C file line number.
 pub trait Logger: std::fmt::Debug {
     /// Defaults to `error!()`.
@@ -260,11 +591,45 @@ pub trait Logger: std::fmt::Debug {
     }
 }
 
+/// The kind of an item named by `ParseCallbacks::item_name`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ItemKind {
+    /// A function.
+    Function,
+    /// A struct, union, enum or typedef.
+    Type,
+    /// A variable or a constant.
+    Var,
+}
+
+/// Hooks choosing the Rust names of the generated items, each one returning
+/// `None` to keep the default name. The functions and variables renamed are
+/// still linked to their C name through `#[link_name]`.
+pub trait ParseCallbacks: std::fmt::Debug {
+    /// The name of a type, function or variable, given its C name.
+    fn item_name(&self, _original: &str, _kind: ItemKind) -> Option<String> {
+        None
+    }
+
+    /// The name of a field of the struct or union `parent`, given their C
+    /// names.
+    fn field_name(&self, _parent: &str, _field: &str) -> Option<String> {
+        None
+    }
+
+    /// The name of a variant of the enum `enum_name`, given the Rust name of
+    /// the enum and the C name of the variant.
+    fn enum_variant_name(&self, _enum_name: &str, _variant: &str) -> Option<String> {
+        None
+    }
+}
+
 /// Contains the generated code.
 #[derive(Clone)]
 pub struct Bindings {
     module: ast::Mod,
     attributes: Vec<ast::Attribute>,
+    static_wrappers: String,
 }
 
 impl Bindings {
@@ -286,8 +651,17 @@ impl Bindings {
         };
 
         let globals = try!(parse_headers(options, logger));
-
-        let (m, attrs) = gen::gen_mod(options, globals, span);
+        try!(filter::mark_enum_styles(options, logger, &globals));
+        try!(macro_types::type_macros(options, logger, &globals));
+        let globals = try!(macro_types::group_macros(options, logger, globals));
+        let globals = try!(filter::filter_globals(options, logger, globals));
+
+        let static_wrappers = if options.wrap_static_fns {
+            gen::gen_static_wrappers(&globals)
+        } else {
+            String::new()
+        };
+        let (m, attrs) = gen::gen_mod(options, logger, globals, span);
         let module = ast::Mod {
             inner: span,
             items: m,
@@ -296,10 +670,15 @@ impl Bindings {
         Ok(Bindings {
             module: module,
             attributes: attrs,
+            static_wrappers: static_wrappers,
         })
     }
 
     /// Get the generated code AST.
+    ///
+    /// The AST has no representation of unions yet, so the native unions
+    /// asked by `Builder::rust_unions` appear as structs marked with a
+    /// `#[bindgen_union]` attribute.
     pub fn into_ast(self) -> Vec<P<ast::Item>> {
         self.module.items
     }
@@ -314,6 +693,19 @@ impl Bindings {
         String::from_utf8(mod_str).unwrap()
     }
 
+    /// Get the C source of the wrappers of the `static` functions, empty
+    /// unless `Builder::wrap_static_fns` is set.
+    pub fn static_wrappers(&self) -> &str {
+        &self.static_wrappers
+    }
+
+    /// Write the C wrappers of the `static` functions in a file, to be
+    /// compiled with the C compiler and linked with the bindings.
+    pub fn write_static_wrappers_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = try!(OpenOptions::new().write(true).truncate(true).create(true).open(path));
+        file.write_all(self.static_wrappers.as_bytes())
+    }
+
     /// Write the generated code in a file.
     pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
         let file = try!(OpenOptions::new().write(true).truncate(true).create(true).open(path));
@@ -326,36 +718,109 @@ impl Bindings {
     pub fn write<'a>(&self, mut writer: Box<Write + 'a>) -> io::Result<()> {
         try!(writer.write("/* automatically generated by rust-bindgen */\n\n".as_bytes()));
         let mut ps = pprust::rust_printer(writer);
-        try!(ps.print_mod(&self.module, &self.attributes));
+        try!(ps.print_inner_attributes(&self.attributes));
+        for item in &self.module.items {
+            try!(print_item(&mut ps, item));
+        }
         try!(ps.print_remaining_comments());
         try!(eof(&mut ps.s));
         ps.s.out.flush()
     }
 }
 
+/// Prints an item, turning the structs marked as native unions back into
+/// unions.
+fn print_item(ps: &mut pprust::State, item: &ast::Item) -> io::Result<()> {
+    if is_union(item) {
+        try!(pp::word(&mut ps.s, &union_to_string(item)));
+        return pp::hardbreak(&mut ps.s);
+    }
+    match item.node {
+        ast::ItemKind::Mod(ref module) if has_union(item) => {
+            try!(pp::word(&mut ps.s, &format!("pub mod {} {{", item.ident.name.as_str())));
+            try!(pp::hardbreak(&mut ps.s));
+            for i in &module.items {
+                try!(print_item(ps, i));
+            }
+            try!(pp::word(&mut ps.s, "}"));
+            pp::hardbreak(&mut ps.s)
+        }
+        _ => ps.print_item(item),
+    }
+}
+
+/// Whether the item is a struct marked as a native union.
+fn is_union(item: &ast::Item) -> bool {
+    match item.node {
+        ast::ItemKind::Struct(..) => item.attrs.iter().any(|a| a.check_name(gen::UNION_MARKER)),
+        _ => false,
+    }
+}
+
+/// Whether the item is a native union or a module containing one.
+fn has_union(item: &ast::Item) -> bool {
+    match item.node {
+        ast::ItemKind::Mod(ref module) => module.items.iter().any(|i| has_union(i)),
+        _ => is_union(item),
+    }
+}
+
+/// Prints a struct marked as a native union as a union, without the marker.
+fn union_to_string(item: &ast::Item) -> String {
+    let mut lines = vec![];
+    for attr in item.attrs.iter().filter(|a| !a.check_name(gen::UNION_MARKER)) {
+        lines.push(pprust::attribute_to_string(attr));
+    }
+    lines.push(format!("pub union {} {{", item.ident.name.as_str()));
+    if let ast::ItemKind::Struct(ref data, _) = item.node {
+        for field in data.fields() {
+            for attr in &field.attrs {
+                lines.push(format!("    {}", pprust::attribute_to_string(attr)));
+            }
+            let name = field.ident.map_or(String::new(), |i| i.name.as_str().to_string());
+            lines.push(format!("    pub {}: {},", name, pprust::ty_to_string(&field.ty)));
+        }
+    }
+    lines.push("}".to_owned());
+    lines.join("\n")
+}
+
 
 #[derive(Debug)]
 struct DummyLogger;
 
 impl Logger for DummyLogger {}
 
-fn parse_headers(options: &BindgenOptions, logger: &Logger) -> Result<Vec<Global>, ()> {
-    fn str_to_ikind(s: &str) -> Option<types::IKind> {
-        match s {
-            "uchar" => Some(types::IUChar),
-            "schar" => Some(types::ISChar),
-            "ushort" => Some(types::IUShort),
-            "sshort" => Some(types::IShort),
-            "uint" => Some(types::IUInt),
-            "sint" => Some(types::IInt),
-            "ulong" => Some(types::IULong),
-            "slong" => Some(types::ILong),
-            "ulonglong" => Some(types::IULongLong),
-            "slonglong" => Some(types::ILongLong),
-            _ => None,
+fn str_to_ikind(s: &str) -> Option<types::IKind> {
+    match s {
+        "uchar" => Some(types::IUChar),
+        "schar" => Some(types::ISChar),
+        "ushort" => Some(types::IUShort),
+        "sshort" => Some(types::IShort),
+        "uint" => Some(types::IUInt),
+        "sint" => Some(types::IInt),
+        "ulong" => Some(types::IULong),
+        "slong" => Some(types::ILong),
+        "ulonglong" => Some(types::IULongLong),
+        "slonglong" => Some(types::ILongLong),
+        _ => None,
+    }
+}
+
+/// The target triple given to clang with `-target` or `--target=`, if any.
+fn clang_target(clang_args: &[String]) -> Option<&str> {
+    let mut target = None;
+    for (i, arg) in clang_args.iter().enumerate() {
+        if arg == "-target" || arg == "--target" {
+            target = clang_args.get(i + 1).map(|t| &t[..]);
+        } else if arg.starts_with("--target=") {
+            target = Some(&arg["--target=".len()..]);
         }
     }
+    target
+}
 
+fn parse_headers(options: &BindgenOptions, logger: &Logger) -> Result<Vec<Global>, ()> {
     let m_ty=parser::MacroTypes{
         t_u8:  str_to_ikind(&options.macro_int_types.0).expect("Invalid C type specified for u8"),
         t_u16: str_to_ikind(&options.macro_int_types.1).expect("Invalid C type specified for u16"),
@@ -377,6 +842,9 @@ fn parse_headers(options: &BindgenOptions, logger: &Logger) -> Result<Vec<Global
         clang_args: options.clang_args.clone(),
         macros: options.convert_macros,
         macro_types: m_ty,
+        fn_macros: options.fn_macros,
+        wrap_static_fns: options.wrap_static_fns,
+        native_int128: options.native_int128,
     };
 
     parser::parse(clang_opts, logger)
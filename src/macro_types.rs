@@ -0,0 +1,390 @@
+//! Choice of the types of the integer macros beyond the magnitude of their
+//! value: by name with `Builder::macro_type`, or from the typedefs and enums
+//! whose name prefixes theirs with `Builder::infer_macro_types`, and grouping
+//! of the prefixed macros into newtypes with `Builder::macro_group`.
+
+use std::cell::RefCell;
+use std::mem;
+use std::os::raw::c_long;
+use std::rc::Rc;
+
+use regex::Regex;
+
+use types::*;
+use {BindgenOptions, Logger, clang_target, str_to_ikind};
+
+/// The integer kind behind a type, if any.
+fn int_kind(ty: &Type) -> Option<IKind> {
+    match *ty {
+        TInt(kind, _) => Some(kind),
+        TNamed(ref ti) => int_kind(&ti.borrow().ty),
+        TEnum(ref ei) => Some(ei.borrow().kind),
+        _ => None,
+    }
+}
+
+/// Whether the enum constants are plain integers, unlike Rust enums.
+fn is_int_type(ty: &Type, options: &BindgenOptions) -> bool {
+    match *ty {
+        TEnum(ref ei) if !ei.borrow().has_int_values(options.rust_enums) => false,
+        _ => int_kind(ty).is_some(),
+    }
+}
+
+/// The layout of a C integer kind on the target given to clang, or else on the
+/// host.
+fn int_layout(kind: IKind, clang_args: &[String]) -> Layout {
+    let size = match kind {
+        IBool | ISChar | IUChar => 1,
+        IShort | IUShort => 2,
+        IInt | IUInt | IWChar => 4,
+        ILong | IULong => long_size(clang_args),
+        ILongLong | IULongLong => 8,
+        I128 | IU128 => 16,
+    };
+    Layout::new(size, size)
+}
+
+/// The size of `long`, 4 bytes on Windows and on the 32-bit targets.
+fn long_size(clang_args: &[String]) -> usize {
+    match clang_target(clang_args) {
+        Some(target) => {
+            let arch = target.split('-').next().unwrap_or("");
+            let is_64_bit = arch.contains("64") || arch == "s390x" || arch == "systemz" ||
+                            arch == "sparcv9";
+            if !is_64_bit || target.contains("windows") || target.ends_with("gnux32") {
+                4
+            } else {
+                8
+            }
+        }
+        None => mem::size_of::<c_long>(),
+    }
+}
+
+/// Whether the value of a macro fits in the integer type of the given kind and
+/// size.
+fn fits_int(val: i64, kind: IKind, size: usize) -> bool {
+    let bits = size * 8;
+    if kind.is_signed() {
+        bits >= 64 || (val >= -(1 << (bits - 1)) && val < (1 << (bits - 1)))
+    } else {
+        val >= 0 && (bits >= 64 || val < (1 << bits))
+    }
+}
+
+/// Finds a C integer type, spelled as for `--override-enum-type`, or an
+/// integer typedef or enum of the headers.
+fn find_type(name: &str, options: &BindgenOptions, globals: &[Global]) -> Option<Type> {
+    if let Some(kind) = str_to_ikind(name) {
+        return Some(TInt(kind, int_layout(kind, &options.clang_args)));
+    }
+    globals.iter()
+           .filter_map(|g| {
+               match *g {
+                   GType(ref ti) if ti.borrow().name == name => Some(TNamed(ti.clone())),
+                   GEnum(ref ei) if ei.borrow().name == name => Some(TEnum(ei.clone())),
+                   _ => None,
+               }
+           })
+           .find(|ty| is_int_type(ty, options))
+}
+
+/// The prefix of the macros grouped by a type, like `open_flags` for
+/// `open_flags_t`.
+fn type_prefix(name: &str) -> String {
+    let name = name.to_lowercase();
+    for suffix in &["_t", "_e", "_type", "_enum"] {
+        if name.len() > suffix.len() && name.ends_with(suffix) {
+            return name[..name.len() - suffix.len()].to_owned();
+        }
+    }
+    name
+}
+
+/// The typedefs and enums the macros can be grouped by, with their prefix.
+fn prefixed_types(options: &BindgenOptions, globals: &[Global]) -> Vec<(String, Type)> {
+    globals.iter()
+           .filter_map(|g| {
+               match *g {
+                   GType(ref ti) => Some((ti.borrow().name.clone(), TNamed(ti.clone()))),
+                   GEnum(ref ei) => Some((ei.borrow().name.clone(), TEnum(ei.clone()))),
+                   _ => None,
+               }
+           })
+           .filter(|&(ref name, ref ty)| !name.is_empty() && is_int_type(ty, options))
+           .map(|(name, ty)| (type_prefix(&name), ty))
+           .collect()
+}
+
+/// Picks the type whose prefix is the longest one of the macro name, up to an
+/// underscore.
+fn infer_type(name: &str, prefixed: &[(String, Type)]) -> Option<Type> {
+    let name = name.to_lowercase();
+    prefixed.iter()
+            .filter(|&&(ref prefix, _)| {
+                name.len() > prefix.len() && name.starts_with(&prefix[..]) &&
+                name.as_bytes()[prefix.len()] == b'_'
+            })
+            .max_by_key(|&&(ref prefix, _)| prefix.len())
+            .map(|&(_, ref ty)| ty.clone())
+}
+
+/// Retypes the integer macros matching `macro_type_overrides` and, with
+/// `infer_macro_types`, the ones sharing their prefix with a type.
+pub fn type_macros(options: &BindgenOptions,
+                   logger: &Logger,
+                   globals: &[Global])
+                   -> Result<(), ()> {
+    let mut overrides = vec![];
+    for &(ref pat, ref ty_name) in &options.macro_type_overrides {
+        let re = match Regex::new(&format!("^(?:{})$", pat)) {
+            Ok(re) => re,
+            Err(e) => {
+                logger.error(&format!("Invalid regex `{}`: {}", pat, e));
+                return Err(());
+            }
+        };
+        match find_type(ty_name, options, globals) {
+            Some(ty) => overrides.push((re, ty)),
+            None => {
+                logger.error(&format!("Unknown integer type `{}` for the macros matching `{}`",
+                                      ty_name,
+                                      pat));
+                return Err(());
+            }
+        }
+    }
+    if overrides.is_empty() && !options.infer_macro_types {
+        return Ok(());
+    }
+
+    let prefixed = if options.infer_macro_types {
+        prefixed_types(options, globals)
+    } else {
+        vec![]
+    };
+    for g in globals {
+        let vi = match *g {
+            GVar(ref vi) if vi.borrow().is_macro => vi,
+            _ => continue,
+        };
+        let mut vi = vi.borrow_mut();
+        let val = match vi.val {
+            Some(ConstVal::Int(val)) => val,
+            _ => continue,
+        };
+        let ty = overrides.iter()
+                          .find(|&&(ref re, _)| re.is_match(&vi.name))
+                          .map(|&(_, ref ty)| ty.clone())
+                          .or_else(|| infer_type(&vi.name, &prefixed));
+        if let Some(ty) = ty {
+            if int_kind(&ty).map_or(false, |kind| fits_int(val, kind, ty.size())) {
+                vi.ty = ty;
+            } else {
+                logger.warn(&format!("The value {} of the macro `{}` doesn't fit in the type it \
+                                      is given, it keeps the type of its magnitude.",
+                                     val,
+                                     vi.name));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A newtype being built from the macros sharing a prefix.
+struct Group {
+    prefix: String,
+    info: Rc<RefCell<TypeInfo>>,
+    items: Vec<(String, i64)>,
+    /// The index of the group among the globals, at its first macro.
+    index: Option<usize>,
+    /// The largest magnitude among the values, whose macro gives the type
+    /// unless the group is the type of a function parameter.
+    magnitude: u64,
+    /// Is the type the one of a function parameter?
+    from_param: bool,
+}
+
+/// Gives `ty`, or the type it points to, the type of a macro group.
+fn group_param_type(ty: &Type, group: &Type) -> Type {
+    match *ty {
+        TPtr(ref t, is_const, layout) => TPtr(Box::new(group_param_type(t, group)), is_const, layout),
+        _ => group.clone(),
+    }
+}
+
+/// The type of a parameter, or the type it points to.
+fn param_value_type(ty: &Type) -> &Type {
+    match *ty {
+        TPtr(ref t, _, _) => param_value_type(t),
+        _ => ty,
+    }
+}
+
+/// Moves the integer macros matching `macro_groups` into `GMacroGroup`s, and
+/// retypes the function parameters of `macro_group_params`.
+pub fn group_macros(options: &BindgenOptions,
+                    logger: &Logger,
+                    globals: Vec<Global>)
+                    -> Result<Vec<Global>, ()> {
+    if options.macro_groups.is_empty() {
+        return Ok(globals);
+    }
+
+    let mut groups: Vec<Group> = options.macro_groups
+                                        .iter()
+                                        .map(|&(ref prefix, ref name)| {
+                                            let ti = TypeInfo::new(name.clone(),
+                                                                   TInt(IInt, Layout::default()),
+                                                                   Layout::default());
+                                            Group {
+                                                prefix: prefix.clone(),
+                                                info: Rc::new(RefCell::new(ti)),
+                                                items: vec![],
+                                                index: None,
+                                                magnitude: 0,
+                                                from_param: false,
+                                            }
+                                        })
+                                        .collect();
+    let mut res = Vec::with_capacity(globals.len());
+    for g in globals {
+        let grouped = match g {
+            GVar(ref vi) => {
+                let vi = vi.borrow();
+                match vi.val {
+                    Some(ConstVal::Int(val)) if vi.is_macro => {
+                        // The longest matching prefix wins, the first one
+                        // given among equals.
+                        groups.iter()
+                              .enumerate()
+                              .rev()
+                              .filter(|&(_, group)| {
+                                  vi.name.len() > group.prefix.len() &&
+                                  vi.name.starts_with(&group.prefix[..])
+                              })
+                              .max_by_key(|&(_, group)| group.prefix.len())
+                              .map(|(i, _)| (i, vi.name.clone(), val, vi.ty.clone()))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        let (i, name, val, ty) = match grouped {
+            Some(grouped) => grouped,
+            None => {
+                res.push(g);
+                continue;
+            }
+        };
+        let group = &mut groups[i];
+        if group.index.is_none() {
+            group.index = Some(res.len());
+            res.push(GOther);
+        }
+        let magnitude = if val < 0 {
+            (val as u64).wrapping_neg()
+        } else {
+            val as u64
+        };
+        if group.items.is_empty() || magnitude > group.magnitude {
+            group.magnitude = magnitude;
+            group.info.borrow_mut().ty = ty;
+        }
+        group.items.push((name[group.prefix.len()..].to_owned(), val));
+    }
+
+    for group in &mut groups {
+        match group.index {
+            Some(i) => {
+                let items = ::std::mem::replace(&mut group.items, vec![]);
+                res[i] = GMacroGroup(group.info.clone(), items);
+            }
+            None => {
+                logger.warn(&format!("No integer macro starts with `{}`, the type `{}` is not \
+                                      generated.",
+                                     group.prefix,
+                                     group.info.borrow().name));
+            }
+        }
+    }
+
+    for &(ref func, ref param, ref name) in &options.macro_group_params {
+        let group = match groups.iter_mut().find(|group| group.info.borrow().name == *name) {
+            Some(group) if group.index.is_some() => group,
+            _ => {
+                logger.error(&format!("Unknown macro group `{}` for the parameter `{}` of `{}`",
+                                      name,
+                                      param,
+                                      func));
+                return Err(());
+            }
+        };
+        let group_ty = TNamed(group.info.clone());
+        let mut found = false;
+        for g in &res {
+            let vi = match *g {
+                GFunc(ref vi) if vi.borrow().name == *func => vi,
+                _ => continue,
+            };
+            if let TFuncPtr(ref mut sig, _) = vi.borrow_mut().ty {
+                for &mut (ref arg_name, ref mut ty) in &mut sig.args {
+                    if arg_name != param {
+                        continue;
+                    }
+                    // The newtype must have the layout of the C parameter.
+                    let param_ty = param_value_type(ty).clone();
+                    let kind = match int_kind(&param_ty) {
+                        Some(kind) if !group.from_param => kind,
+                        Some(_) if group.info.borrow().ty.layout() == param_ty.layout() => {
+                            *ty = group_param_type(ty, &group_ty);
+                            found = true;
+                            continue;
+                        }
+                        _ => {
+                            logger.error(&format!("The parameter `{}` of `{}` can't have the \
+                                                   type `{}`, whose layout differs from its \
+                                                   C type.",
+                                                  param,
+                                                  func,
+                                                  name));
+                            return Err(());
+                        }
+                    };
+                    let items = match res[group.index.unwrap()] {
+                        GMacroGroup(_, ref items) => items,
+                        _ => unreachable!(),
+                    };
+                    if let Some(&(ref item, _)) = items.iter().find(|&&(_, val)| {
+                        !fits_int(val, kind, param_ty.size())
+                    }) {
+                        logger.error(&format!("The macro `{}{}` doesn't fit in the type of the \
+                                               parameter `{}` of `{}`.",
+                                              group.prefix,
+                                              item,
+                                              param,
+                                              func));
+                        return Err(());
+                    }
+                    {
+                        let mut info = group.info.borrow_mut();
+                        info.layout = param_ty.layout();
+                        info.ty = param_ty;
+                    }
+                    group.from_param = true;
+                    *ty = group_param_type(ty, &group_ty);
+                    found = true;
+                }
+            }
+        }
+        if !found {
+            logger.warn(&format!("No parameter `{}` of a function `{}` to give the type `{}`.",
+                                 param,
+                                 func,
+                                 name));
+        }
+    }
+    Ok(res)
+}
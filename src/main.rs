@@ -6,7 +6,7 @@ extern crate docopt;
 extern crate rustc_serialize;
 extern crate env_logger;
 
-use bindgen::{Builder, LinkType};
+use bindgen::{Builder, FnMacroStyle, LinkType};
 use std::io::{self, Write};
 use std::fs::File;
 use std::process::exit;
@@ -17,6 +17,7 @@ Generate C bindings for Rust.
 Usage:
   bindgen [options] <file> [-- <clang-args>...]
   bindgen [options] (--match=<name> ...) <file> [-- <clang-args>...]
+  bindgen [options] [--allowlist-function=<regex> ...] [--allowlist-type=<regex> ...] [--allowlist-var=<regex> ...] [--blocklist-function=<regex> ...] [--blocklist-type=<regex> ...] [--blocklist-var=<regex> ...] [--opaque-type=<regex> ...] [--newtype-enum=<regex> ...] [--constified-enum-module=<regex> ...] [--bitfield-enum=<regex> ...] [--macro-type=<regex=type> ...] [--macro-group=<prefix=name> ...] [--macro-group-param=<func:param=name> ...] [--flexible-array-len=<struct=field> ...] <file> [-- <clang-args>...]
   bindgen (-h | --help)
 
 Options:
@@ -31,6 +32,22 @@ Options:
                                whose name contains <name>
                                If multiple -match options are provided, files
                                matching any rule are bound to.
+  --allowlist-function=<regex>
+                               Only output bindings for the functions matching
+                               <regex> and the types they depend on.
+  --allowlist-type=<regex>     Only output bindings for the types matching
+                               <regex> and the types they depend on.
+  --allowlist-var=<regex>      Only output bindings for the variables matching
+                               <regex> and the types they depend on.
+  --blocklist-function=<regex> Never output bindings for the functions matching
+                               <regex>.
+  --blocklist-type=<regex>     Never output bindings for the types matching
+                               <regex>.
+  --blocklist-var=<regex>      Never output bindings for the variables matching
+                               <regex>.
+  --opaque-type=<regex>        Output the structs, unions and typedefs matching
+                               <regex> as opaque blobs of the same size and
+                               alignment.
   --builtins                   Output bindings for builtin definitions
                                (for example __builtin_va_list)
   --emit-clang-ast             Output the ast (for debugging purposes)
@@ -54,9 +71,53 @@ Options:
                               `libfoo_`. The removal is case-insensitive.
   --no-derive-debug           Disable `derive(Debug)` for all generated types.
   --no-rust-enums             Convert C enums to Rust constants instead of enums.
+  --newtype-enum=<regex>      Convert the C enums matching <regex> to newtypes
+                              with associated constants.
+  --constified-enum-module=<regex>
+                              Convert the C enums matching <regex> to modules
+                              of constants.
+  --bitfield-enum=<regex>     Convert the C enums matching <regex> to newtypes
+                              with associated constants and bitwise operators.
+  --strip-enum-prefix         Strip the prefix shared by all the variants of
+                              an enum.
+  --camel-case-enum-variants  Convert the enum variants to UpperCamelCase.
+  --native-int128             Convert `__int128` to `i128` instead of a struct.
+  --arch-vector-types         Convert the SIMD vectors to the x86 intrinsic
+                              types of `core::arch` when possible.
+  --complex-type=<path>       Use the generic type <path>, like
+                              `num_complex::Complex`, for the C complex numbers.
+  --flexible-array-len=<struct=field>
+                              Generate the methods returning the flexible array
+                              member of <struct> as a slice of <field> elements.
+  --rust-unions               Convert C unions to Rust unions instead of structs
+                              with accessors.
+  --wrap-static-fns=<file>    Bind the `static` functions through exported C
+                              wrappers, whose source is written to <file>.
+  --layout-tests              Generate tests checking the layout of the structs
+                              and unions against the one computed by clang.
+  --no-doc-comments           Do not copy the C documentation comments into the
+                              generated code.
+  --strip-doxygen             Drop the Doxygen commands from the copied comments,
+                              listing their parameters in Markdown.
   --dont-convert-floats       Disables the convertion of C `float` and `double`
                               to Rust `f32` and `f64`.
   --convert-macros            Try to convert macros into const definitions
+  --macro-strings-as-str      Convert the string macros to `&str` instead of
+                              nul-terminated byte strings.
+  --macro-type=<regex=type>   Give the macros matching <regex> the C integer
+                              type <type>, spelled as for `--override-enum-type`,
+                              or the integer typedef or enum named <type>.
+  --infer-macro-types         Give the macros the type of the typedef or enum
+                              whose name prefixes theirs.
+  --macro-group=<prefix=name>
+                              Collect the macros starting with <prefix> into a
+                              bitflags-like newtype <name>.
+  --macro-group-param=<func:param=name>
+                              Give the parameter <param> of the function <func>
+                              the type of the macro group <name>.
+  --fn-macros=<style>         Convert the arithmetic function-like macros to
+                              `macro_rules!` macros (`macro-rules`) or to inline
+                              functions on `i64` (`inline-fn`).
   --macro-int-types=<ty,...>  When converting macros, convert integers that
                               would fit in a u8,u16,u32,u64,i8,i16,i32,i64 to
                               the corresponding named C type, respectively. See
@@ -70,6 +131,13 @@ struct Args {
     flag_link: Option<String>,
     flag_output: String,
     flag_match: Vec<String>,
+    flag_allowlist_function: Vec<String>,
+    flag_allowlist_type: Vec<String>,
+    flag_allowlist_var: Vec<String>,
+    flag_blocklist_function: Vec<String>,
+    flag_blocklist_type: Vec<String>,
+    flag_blocklist_var: Vec<String>,
+    flag_opaque_type: Vec<String>,
     flag_builtins: bool,
     flag_emit_clang_ast: bool,
     flag_override_enum_type: String,
@@ -79,8 +147,28 @@ struct Args {
     // TODO: allow finer control.
     flag_no_derive_debug: bool,
     flag_no_rust_enums: bool,
+    flag_newtype_enum: Vec<String>,
+    flag_constified_enum_module: Vec<String>,
+    flag_bitfield_enum: Vec<String>,
+    flag_strip_enum_prefix: bool,
+    flag_camel_case_enum_variants: bool,
+    flag_native_int128: bool,
+    flag_arch_vector_types: bool,
+    flag_complex_type: Option<String>,
+    flag_flexible_array_len: Vec<String>,
+    flag_rust_unions: bool,
+    flag_wrap_static_fns: Option<String>,
+    flag_layout_tests: bool,
+    flag_no_doc_comments: bool,
+    flag_strip_doxygen: bool,
     flag_dont_convert_floats: bool,
     flag_convert_macros: bool,
+    flag_macro_strings_as_str: bool,
+    flag_fn_macros: Option<String>,
+    flag_macro_type: Vec<String>,
+    flag_infer_macro_types: bool,
+    flag_macro_group: Vec<String>,
+    flag_macro_group_param: Vec<String>,
     flag_macro_int_types: Option<String>,
 }
 
@@ -94,20 +182,130 @@ fn args_to_opts(args: Args) -> Builder<'static> {
            .use_core(args.flag_use_core)
            .derive_debug(!args.flag_no_derive_debug)
            .rust_enums(!args.flag_no_rust_enums)
+           .strip_enum_prefix(args.flag_strip_enum_prefix)
+           .camel_case_enum_variants(args.flag_camel_case_enum_variants)
+           .native_int128(args.flag_native_int128)
+           .arch_vector_types(args.flag_arch_vector_types)
+           .rust_unions(args.flag_rust_unions)
+           .wrap_static_fns(args.flag_wrap_static_fns.is_some())
+           .layout_tests(args.flag_layout_tests)
+           .generate_comments(!args.flag_no_doc_comments)
+           .strip_doxygen(args.flag_strip_doxygen)
            .override_enum_ty(args.flag_override_enum_type)
-           .convert_macros(args.flag_convert_macros);
+           .convert_macros(args.flag_convert_macros)
+           .macro_strings_as_str(args.flag_macro_strings_as_str)
+           .infer_macro_types(args.flag_infer_macro_types);
     for arg in args.arg_clang_args {
         builder.clang_arg(arg);
     }
     for flag_match in args.flag_match {
         builder.match_pat(flag_match);
     }
+    for re in args.flag_allowlist_function {
+        builder.allowlist_function(re);
+    }
+    for re in args.flag_allowlist_type {
+        builder.allowlist_type(re);
+    }
+    for re in args.flag_allowlist_var {
+        builder.allowlist_var(re);
+    }
+    for re in args.flag_blocklist_function {
+        builder.blocklist_function(re);
+    }
+    for re in args.flag_blocklist_type {
+        builder.blocklist_type(re);
+    }
+    for re in args.flag_blocklist_var {
+        builder.blocklist_var(re);
+    }
+    for re in args.flag_opaque_type {
+        builder.opaque_type(re);
+    }
+    for re in args.flag_newtype_enum {
+        builder.newtype_enum(re);
+    }
+    for re in args.flag_constified_enum_module {
+        builder.constified_enum_module(re);
+    }
+    for re in args.flag_bitfield_enum {
+        builder.bitfield_enum(re);
+    }
     if let Some(s) = args.flag_remove_prefix {
         builder.remove_prefix(s);
     }
     if let Some(s) = args.flag_macro_int_types {
         builder.macro_int_types(s.split(','));
     }
+    for pair in args.flag_macro_type {
+        let mut parts = pair.rsplitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(ty), Some(re)) => {
+                builder.macro_type(re, ty);
+            }
+            _ => {
+                println!("Wrong macro type format: {}", pair);
+                exit(1);
+            }
+        }
+    }
+    for pair in args.flag_macro_group {
+        let mut parts = pair.rsplitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(name), Some(prefix)) => {
+                builder.macro_group(prefix, name);
+            }
+            _ => {
+                println!("Wrong macro group format: {}", pair);
+                exit(1);
+            }
+        }
+    }
+    for spec in args.flag_macro_group_param {
+        let mut parts = spec.rsplitn(2, '=');
+        let (name, param) = match (parts.next(), parts.next()) {
+            (Some(name), Some(param)) => (name, param),
+            _ => {
+                println!("Wrong macro group parameter format: {}", spec);
+                exit(1);
+            }
+        };
+        let mut parts = param.splitn(2, ':');
+        match (parts.next(), parts.next()) {
+            (Some(func), Some(param)) => {
+                builder.macro_group_param(func, param, name);
+            }
+            _ => {
+                println!("Wrong macro group parameter format: {}", spec);
+                exit(1);
+            }
+        }
+    }
+    for pair in args.flag_flexible_array_len {
+        let mut parts = pair.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(name), Some(len_field)) => {
+                builder.flexible_array_len(name, len_field);
+            }
+            _ => {
+                println!("Wrong flexible array length format: {}", pair);
+                exit(1);
+            }
+        }
+    }
+    if let Some(path) = args.flag_complex_type {
+        builder.complex_type(path);
+    }
+    if let Some(style) = args.flag_fn_macros {
+        builder.fn_macros(match &style[..] {
+            "macro-rules" => FnMacroStyle::MacroRules,
+            "inline-fn" => FnMacroStyle::InlineFn,
+            _ => {
+                println!("Function-like macro style unknown: {}", style);
+                exit(1);
+            }
+        });
+    }
     if args.flag_builtins {
         builder.builtins();
     }
@@ -157,6 +355,7 @@ pub fn main() {
     debug!("{:?}", args);
 
     let output = get_output(&args.flag_output);
+    let wrappers_output = args.flag_wrap_static_fns.clone();
 
     let builder = args_to_opts(args);
     debug!("{:?}", builder);
@@ -170,6 +369,12 @@ pub fn main() {
                     exit(-1);
                 }
             }
+            if let Some(path) = wrappers_output {
+                if let Err(e) = bindings.write_static_wrappers_to_file(&path) {
+                    error!("Unable to write the static wrappers to {}. {}", path, e);
+                    exit(-1);
+                }
+            }
         }
         Err(()) => exit(-1),
     }
@@ -3,6 +3,8 @@
 use std::collections::{HashMap, HashSet};
 use std::collections::hash_map;
 use std::cell::RefCell;
+use std::cmp;
+use std::mem;
 use std::ops::Deref;
 use std::rc::Rc;
 
@@ -14,8 +16,10 @@ use types as il;
 use types::*;
 use clang as cx;
 use clang::{Cursor, Diagnostic, TranslationUnit, ast_dump};
+use cexpr::token::{Kind as CexprTokenKind, Token as CexprToken};
 
-use super::Logger;
+use super::{FnMacroStyle, Logger};
+use super::clang_target;
 
 pub struct MacroTypes {
     pub t_u8:  IKind,
@@ -38,6 +42,9 @@ pub struct ClangParserOptions {
     pub clang_args: Vec<String>,
     pub macros: bool,
     pub macro_types: MacroTypes,
+    pub fn_macros: FnMacroStyle,
+    pub wrap_static_fns: bool,
+    pub native_int128: bool,
 }
 
 struct ClangParserCtx<'a> {
@@ -48,6 +55,12 @@ struct ClangParserCtx<'a> {
     defined_macros: HashMap<Vec<u8>,::cexpr::expr::EvalResult>,
     logger: &'a (Logger + 'a),
     err_count: i32,
+    /// The structs standing for `__int128` and `unsigned __int128`, by
+    /// signedness, without `native_int128`.
+    int128_comps: HashMap<bool, Rc<RefCell<CompInfo>>>,
+    /// The opaque structs standing for the unsupported types, by size and
+    /// alignment.
+    unsupported_comps: HashMap<(usize, usize), Rc<RefCell<CompInfo>>>,
 }
 
 fn match_pattern(ctx: &mut ClangParserCtx, cursor: &Cursor) -> bool {
@@ -89,7 +102,7 @@ fn decl_name(ctx: &mut ClangParserCtx, cursor: &Cursor) -> Global {
             debug!("type `{}` = {:?}; // {:?}", spelling, ty, layout);
 
             let glob_decl = match cursor.kind() {
-                CXCursorKind::StructDecl => {
+                CXCursorKind::StructDecl | CXCursorKind::ClassDecl => {
                     let ci = Rc::new(RefCell::new(CompInfo::new(spelling,
                                                                 CompKind::Struct,
                                                                 vec![],
@@ -141,6 +154,15 @@ fn decl_name(ctx: &mut ClangParserCtx, cursor: &Cursor) -> Global {
                 _ => GOther,
             };
 
+            let namespace = cursor_namespace(&cursor);
+            match glob_decl {
+                GCompDecl(ref ci) => ci.borrow_mut().namespace = namespace,
+                GEnumDecl(ref ei) => ei.borrow_mut().namespace = namespace,
+                GType(ref ti) => ti.borrow_mut().namespace = namespace,
+                GVar(ref vi) | GFunc(ref vi) => vi.borrow_mut().namespace = namespace,
+                _ => {}
+            }
+
             e.insert(glob_decl.clone());
             glob_decl
         }
@@ -153,6 +175,48 @@ fn decl_name(ctx: &mut ClangParserCtx, cursor: &Cursor) -> Global {
     decl
 }
 
+/// The names of the C++ namespaces enclosing a declaration, outermost first.
+fn cursor_namespace(cursor: &Cursor) -> Vec<String> {
+    let mut namespace = vec![];
+    let mut parent = cursor.semantic_parent();
+    loop {
+        match parent.kind() {
+            CXCursorKind::Namespace => {
+                // The anonymous namespaces don't introduce a module.
+                let name = parent.spelling();
+                if !name.is_empty() {
+                    namespace.push(name);
+                }
+            }
+            // Nested types are generated next to their parent.
+            CXCursorKind::StructDecl |
+            CXCursorKind::ClassDecl |
+            CXCursorKind::UnionDecl |
+            CXCursorKind::LinkageSpec |
+            CXCursorKind::UnexposedDecl => {}
+            _ => break,
+        }
+        parent = parent.semantic_parent();
+    }
+    namespace.reverse();
+    namespace
+}
+
+/// The mangled symbol name of a C++ declaration, `None` if it has C linkage.
+fn cxx_mangling(cursor: &Cursor) -> Option<String> {
+    let mut mangled = cursor.mangling();
+    // The symbols are prefixed by an underscore on Apple platforms, which
+    // rustc adds back.
+    if cfg!(target_os = "macos") && mangled.starts_with('_') {
+        mangled.remove(0);
+    }
+    if mangled.starts_with("_Z") || mangled.starts_with('?') {
+        Some(mangled)
+    } else {
+        None
+    }
+}
+
 fn opaque_decl(ctx: &mut ClangParserCtx, decl: &Cursor) {
     let name = decl_name(ctx, decl);
     ctx.globals.push(name);
@@ -209,14 +273,17 @@ fn conv_ptr_ty(ctx: &mut ClangParserCtx,
 
 fn mk_fn_sig(ctx: &mut ClangParserCtx, ty: &cx::Type, cursor: &Cursor) -> il::FuncSig {
     let args_lst: Vec<(String, il::Type)> = match cursor.kind() {
-        CXCursorKind::FunctionDecl => {
+        CXCursorKind::FunctionDecl |
+        CXCursorKind::CXXMethod |
+        CXCursorKind::Constructor |
+        CXCursorKind::Destructor => {
             // For CXCursorKind::FunctionDecl, cursor.args() is the reliable way to
             // get parameter names and types.
             cursor.args()
                   .iter()
                   .map(|arg| {
                       let arg_name = arg.spelling();
-                      (arg_name, conv_ty(ctx, &arg.cur_type(), arg))
+                      (arg_name, conv_param_ty(ctx, &arg.cur_type(), arg))
                   })
                   .collect()
         }
@@ -226,7 +293,7 @@ fn mk_fn_sig(ctx: &mut ClangParserCtx, ty: &cx::Type, cursor: &Cursor) -> il::Fu
             let mut args_lst = vec![];
             cursor.visit(|c: &Cursor, _: &Cursor| {
                 if c.kind() == CXCursorKind::ParmDecl {
-                    args_lst.push((c.spelling(), conv_ty(ctx, &c.cur_type(), c)));
+                    args_lst.push((c.spelling(), conv_param_ty(ctx, &c.cur_type(), c)));
                 }
                 CXChildVisitResult::Continue
             });
@@ -237,6 +304,21 @@ fn mk_fn_sig(ctx: &mut ClangParserCtx, ty: &cx::Type, cursor: &Cursor) -> il::Fu
     let ret_ty = Box::new(conv_ty(ctx, &ty.ret_type(), cursor));
     let abi = get_abi(ty.call_conv());
 
+    if ret_ty.is_blob_float() || args_lst.iter().any(|&(_, ref ty)| ty.is_blob_float()) {
+        let msg = format!("`{}` passes a `long double` or `__float128` by value, which its \
+                           binding does with the wrong ABI ({}).",
+                          cursor.spelling(),
+                          cursor.location());
+        ctx.logger.warn(&msg[..]);
+    }
+    if ret_ty.is_unsupported() || args_lst.iter().any(|&(_, ref ty)| ty.is_unsupported()) {
+        let msg = format!("`{}` passes by value a type bindgen doesn't support, which its \
+                           binding does with the wrong ABI ({}).",
+                          cursor.spelling(),
+                          cursor.location());
+        ctx.logger.warn(&msg[..]);
+    }
+
     // Function is presumed unsafe if it takes a pointer argument.
     let is_unsafe = args_lst.iter().any(|arg| {
         match arg.1 {
@@ -254,9 +336,33 @@ fn mk_fn_sig(ctx: &mut ClangParserCtx, ty: &cx::Type, cursor: &Cursor) -> il::Fu
     }
 }
 
+/// Converts the type of a function parameter. A variable-length array, like
+/// `buf` in `void fill(int n, double buf[n])`, is adjusted to a pointer to its
+/// elements like the other array parameters. Its inner variable dimensions, as
+/// in `double buf[n][m]` or `double (*buf)[m]`, can't be expressed in Rust and
+/// are folded into that pointer.
+fn conv_param_ty(ctx: &mut ClangParserCtx, ty: &cx::Type, cursor: &Cursor) -> il::Type {
+    let (mut elem, layout) = match ty.kind() {
+        CXTypeKind::VariableArray => {
+            // Only the pointer type of the function type knows the target
+            // layout, which doesn't matter for a parameter.
+            (ty.elem_type(), Layout::new(mem::size_of::<*mut u8>(), mem::align_of::<*mut u8>()))
+        }
+        CXTypeKind::Pointer if ty.pointee_type().kind() == CXTypeKind::VariableArray => {
+            (ty.pointee_type(), Layout::new(ty.size(), ty.align()))
+        }
+        _ => return conv_ty(ctx, ty, cursor),
+    };
+    while elem.kind() == CXTypeKind::VariableArray {
+        elem = elem.elem_type();
+    }
+    let is_const = elem.is_const();
+    TPtr(Box::new(conv_ty(ctx, &elem, cursor)), is_const, layout)
+}
+
 fn conv_decl_ty(ctx: &mut ClangParserCtx, cursor: &Cursor) -> il::Type {
     match cursor.kind() {
-        CXCursorKind::StructDecl | CXCursorKind::UnionDecl => {
+        CXCursorKind::StructDecl | CXCursorKind::ClassDecl | CXCursorKind::UnionDecl => {
             let decl = decl_name(ctx, cursor);
             let ci = decl.compinfo();
             TComp(ci)
@@ -302,35 +408,88 @@ fn conv_ty(ctx: &mut ClangParserCtx, ty: &cx::Type, cursor: &Cursor) -> il::Type
         CXTypeKind::LongLong => TInt(ILongLong, layout),
         CXTypeKind::WChar => TInt(IWChar, layout),
         CXTypeKind::Float => TFloat(FFloat, layout),
-        CXTypeKind::Double | CXTypeKind::LongDouble => TFloat(FDouble, layout),
+        CXTypeKind::Double => TFloat(FDouble, layout),
+        // `long double` is a `double` on some targets, like MSVC.
+        CXTypeKind::LongDouble if layout.size == 8 => TFloat(FDouble, layout),
+        CXTypeKind::LongDouble => TFloat(FLongDouble, layout),
         CXTypeKind::Pointer => conv_ptr_ty(ctx, &ty.pointee_type(), cursor, layout),
-        CXTypeKind::VariableArray => unreachable!(),
+        CXTypeKind::VariableArray => {
+            log_err_warn(ctx,
+                         &format!("variable-length array `{}` outside of a function \
+                                   parameter ({})",
+                                  cursor.spelling(),
+                                  cursor.location())[..],
+                         true);
+            TVoid
+        }
         CXTypeKind::DependentSizedArray | CXTypeKind::IncompleteArray => {
             TArray(Box::new(conv_ty(ctx, &ty.elem_type(), cursor)), 0, layout)
         }
         CXTypeKind::FunctionProto | CXTypeKind::FunctionNoProto => {
             TFuncProto(mk_fn_sig(ctx, ty, cursor), layout)
         }
+        // This libclang has no kind for the atomics, only their spelling. The
+        // typedefs of atomics keep their name.
+        _ if ty.kind() != CXTypeKind::Typedef && atomic_name(ty).is_some() => {
+            conv_atomic_ty(ctx, ty, cursor, layout)
+        }
         CXTypeKind::Record |
         CXTypeKind::Typedef |
         CXTypeKind::Unexposed |
         CXTypeKind::Enum => conv_decl_ty(ctx, &ty.declaration()),
-        CXTypeKind::ConstantArray | CXTypeKind::Vector => {
+        CXTypeKind::ConstantArray => {
             TArray(Box::new(conv_ty(ctx, &ty.elem_type(), cursor)),
                    ty.elem_num(),
                    layout)
         }
+        CXTypeKind::Vector => {
+            TVector(Box::new(conv_ty(ctx, &ty.elem_type(), cursor)),
+                    ty.elem_num(),
+                    layout)
+        }
         CXTypeKind::Int128 | CXTypeKind::UInt128 => {
-            let compinfo = Rc::new(RefCell::new(CompInfo::new(cursor.spelling(), CompKind::Struct, vec![
-                CompMember::Field(FieldInfo::new(String::from("hi"),
-                    TInt(if ty.kind() == CXTypeKind::Int128 { ILongLong } else { IULongLong }, Layout::new(8, 8)), None)),
-                CompMember::Field(FieldInfo::new(String::from("lo"), TInt(IULongLong, Layout::new(8, 8)), None)),
-            ], layout)));
+            let signed = ty.kind() == CXTypeKind::Int128;
+            if ctx.options.native_int128 {
+                return TInt(if signed { I128 } else { IU128 }, layout);
+            }
+            if let Some(compinfo) = ctx.int128_comps.get(&signed) {
+                return TComp(compinfo.clone());
+            }
+
+            let name = if signed { "__BindgenInt128" } else { "__BindgenUInt128" };
+            let hi = CompMember::Field(FieldInfo::new(String::from("hi"),
+                TInt(if signed { ILongLong } else { IULongLong }, Layout::new(8, 8)), None));
+            let lo = CompMember::Field(FieldInfo::new(String::from("lo"), TInt(IULongLong, Layout::new(8, 8)), None));
+            // The halves are in memory order, and the struct keeps the
+            // alignment of `__int128`, not the one of its halves.
+            let members = if is_big_endian(&ctx.options.clang_args) {
+                vec![hi, lo]
+            } else {
+                vec![lo, hi]
+            };
+            let layout = Layout { explicit_align: Some(layout.align), ..layout };
+            let compinfo = Rc::new(RefCell::new(CompInfo::new(name.to_owned(), CompKind::Struct, members, layout)));
 
             ctx.globals.push(GComp(compinfo.clone()));
+            ctx.int128_comps.insert(signed, compinfo.clone());
 
             TComp(compinfo)
         }
+        CXTypeKind::Complex => {
+            match conv_ty(ctx, &ty.elem_type(), cursor) {
+                TFloat(kind, _) => TComplex(kind, layout),
+                _ => {
+                    let fail = ctx.options.fail_on_unknown_type;
+                    log_err_warn(ctx,
+                                 &format!("unsupported complex integer type ({})",
+                                          cursor.location())[..],
+                                 fail);
+                    unsupported_ty(ctx, layout)
+                }
+            }
+        }
+        // Not every version of libclang has a kind for it.
+        _ if ty.name() == "__float128" || ty.name() == "_Float128" => TFloat(FFloat128, layout),
         _ => {
             let fail = ctx.options.fail_on_unknown_type;
             log_err_warn(ctx,
@@ -338,11 +497,191 @@ fn conv_ty(ctx: &mut ClangParserCtx, ty: &cx::Type, cursor: &Cursor) -> il::Type
                                   ty.kind(),
                                   cursor.location())[..],
                          fail);
-            TVoid
+            unsupported_ty(ctx, layout)
         }
     }
 }
 
+/// Returns the spelling of the value type of an atomic, like `unsigned int` for
+/// `_Atomic(unsigned int)`.
+fn atomic_value_name(name: &str) -> Option<&str> {
+    let name = name.trim_left_matches("const ").trim_left_matches("volatile ");
+    if name.starts_with("_Atomic(") && name.ends_with(')') {
+        Some(name["_Atomic(".len()..name.len() - 1].trim())
+    } else {
+        None
+    }
+}
+
+/// The spelling of an atomic type, or else of its canonical type when the
+/// atomic is hidden behind a typedef, like `atomic_int`.
+fn atomic_name(ty: &cx::Type) -> Option<String> {
+    let name = ty.name();
+    if atomic_value_name(&name).is_some() {
+        return Some(name);
+    }
+    let canonical_name = ty.canonical_type().name();
+    if atomic_value_name(&canonical_name).is_some() {
+        Some(canonical_name)
+    } else {
+        None
+    }
+}
+
+/// Converts an atomic integer or pointer whose size is its alignment, like the
+/// Rust atomics. The others are made opaque.
+fn conv_atomic_ty(ctx: &mut ClangParserCtx,
+                  ty: &cx::Type,
+                  cursor: &Cursor,
+                  layout: Layout)
+                  -> il::Type {
+    let name = atomic_name(ty).unwrap();
+    let value_name = atomic_value_name(&name).unwrap();
+    let char_signed = is_char_signed(&ctx.options.clang_args);
+    let value_ty = if value_name.ends_with('*') {
+        let pointee_name = value_name[..value_name.len() - 1].trim();
+        let is_const = pointee_name.starts_with("const ");
+        let pointee_name = pointee_name.trim_left_matches("const ");
+        let pointee = match builtin_ikind(pointee_name, char_signed) {
+            Some(kind) => Some(TInt(kind, Layout::new(0, 0))),
+            None if pointee_name == "void" => Some(TVoid),
+            // The type reference of the declaration is only the pointee of a
+            // single level of pointer.
+            None if pointee_name.contains('*') => None,
+            None => atomic_type_ref(ctx, cursor),
+        };
+        pointee.map(|pointee| TPtr(Box::new(pointee), is_const, layout))
+    } else {
+        match builtin_ikind(value_name, char_signed) {
+            Some(kind) => Some(TInt(kind, layout)),
+            None => atomic_type_ref(ctx, cursor),
+        }
+    };
+
+    let is_lock_free_size = match layout.size {
+        1 | 2 | 4 | 8 => layout.align == layout.size,
+        _ => false,
+    };
+    match value_ty.as_ref().and_then(|ty| ty.atomic_value()) {
+        Some(TInt(IBool, _)) if layout.size != 1 => {}
+        Some(_) if is_lock_free_size => return TAtomic(Box::new(value_ty.unwrap()), layout),
+        _ => {}
+    }
+
+    let msg = format!("`{}` has no Rust atomic equivalent and is made opaque ({})",
+                      name,
+                      cursor.location());
+    ctx.logger.warn(&msg[..]);
+    unsupported_ty(ctx, layout)
+}
+
+/// The named type an atomic in a declaration refers to, as in
+/// `_Atomic(uint32_t) count;`.
+fn atomic_type_ref(ctx: &mut ClangParserCtx, cursor: &Cursor) -> Option<il::Type> {
+    match cursor.kind() {
+        CXCursorKind::FieldDecl |
+        CXCursorKind::VarDecl |
+        CXCursorKind::ParmDecl |
+        CXCursorKind::TypedefDecl => {}
+        _ => return None,
+    }
+    let mut type_ref = None;
+    cursor.visit(|c: &Cursor, _: &Cursor| {
+        if c.kind() == CXCursorKind::TypeRef {
+            type_ref = Some(*c);
+            CXChildVisitResult::Break
+        } else {
+            CXChildVisitResult::Continue
+        }
+    });
+    type_ref.map(|c| conv_ty(ctx, &c.cur_type(), &c))
+}
+
+/// Whether the target given to clang, or else the host, is big-endian.
+fn is_big_endian(clang_args: &[String]) -> bool {
+    match clang_target(clang_args) {
+        Some(target) => {
+            let arch = target.split('-').next().unwrap_or("");
+            match arch {
+                "powerpc" | "powerpc64" | "ppc" | "ppc64" | "mips" | "mips64" | "sparc" |
+                "sparc64" | "sparcv9" | "s390x" | "systemz" | "armeb" | "thumbeb" |
+                "aarch64_be" | "m68k" => true,
+                _ => false,
+            }
+        }
+        None => cfg!(target_endian = "big"),
+    }
+}
+
+/// Whether a plain `char` is signed on the target given to clang, or else on
+/// the host.
+fn is_char_signed(clang_args: &[String]) -> bool {
+    match clang_target(clang_args) {
+        Some(target) => {
+            let arch = target.split('-').next().unwrap_or("");
+            let unsigned_arch = arch.starts_with("arm") || arch.starts_with("thumb") ||
+                                arch.starts_with("aarch64") ||
+                                arch.starts_with("powerpc") ||
+                                arch.starts_with("ppc") || arch.starts_with("riscv") ||
+                                arch == "s390x" || arch == "systemz" ||
+                                arch == "hexagon" || arch == "msp430";
+            // Apple and Windows keep a signed `char` on every architecture.
+            !unsigned_arch || target.contains("apple") || target.contains("darwin") ||
+            target.contains("windows")
+        }
+        None => (!(0 as ::std::os::raw::c_char) as i32) < 0,
+    }
+}
+
+/// The kind of a builtin integer type from its spelling, `char_signed` telling
+/// the signedness of a plain `char`.
+fn builtin_ikind(name: &str, char_signed: bool) -> Option<IKind> {
+    let kind = match name {
+        "_Bool" | "bool" => IBool,
+        "char" if char_signed => ISChar,
+        "char" => IUChar,
+        "signed char" => ISChar,
+        "unsigned char" => IUChar,
+        "short" => IShort,
+        "unsigned short" => IUShort,
+        "int" => IInt,
+        "unsigned int" => IUInt,
+        "long" => ILong,
+        "unsigned long" => IULong,
+        "long long" => ILongLong,
+        "unsigned long long" => IULongLong,
+        "wchar_t" => IWChar,
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// Returns an opaque struct with the size and the alignment of an unsupported
+/// type, so that the fields after it keep their offsets. The types of unknown
+/// size are still `void`.
+fn unsupported_ty(ctx: &mut ClangParserCtx, layout: Layout) -> il::Type {
+    if layout.size == 0 {
+        return TVoid;
+    }
+    let key = (layout.size, layout.align);
+    if let Some(compinfo) = ctx.unsupported_comps.get(&key) {
+        return TComp(compinfo.clone());
+    }
+
+    let name = format!("{}{}x{}", UNSUPPORTED_PREFIX, layout.size, layout.align);
+    let mut ci = CompInfo::new(name, CompKind::Struct, vec![], layout);
+    ci.opaque = true;
+    ci.comment = format!("/// A C type not supported by bindgen, of {} bytes aligned to {}.",
+                         layout.size,
+                         layout.align);
+    let compinfo = Rc::new(RefCell::new(ci));
+
+    ctx.globals.push(GComp(compinfo.clone()));
+    ctx.unsupported_comps.insert(key, compinfo.clone());
+
+    TComp(compinfo)
+}
+
 fn opaque_ty(ctx: &mut ClangParserCtx, ty: &cx::Type) {
     if ty.kind() == CXTypeKind::Record || ty.kind() == CXTypeKind::Enum {
         let decl = ty.declaration();
@@ -354,8 +693,9 @@ fn opaque_ty(ctx: &mut ClangParserCtx, ty: &cx::Type) {
 }
 
 impl MacroTypes {
-    fn infer(&self, val: &::cexpr::expr::EvalResult) -> Option<(Type,Option<i64>)> {
+    fn infer(&self, val: &::cexpr::expr::EvalResult) -> Option<(Type,Option<ConstVal>)> {
         use cexpr::expr::EvalResult::*;
+        use cexpr::literal::CChar;
         match *val {
             Int(::std::num::Wrapping(i)) => {
                 let kind=if i>=0 {
@@ -379,13 +719,71 @@ impl MacroTypes {
                         self.t_i64
                     }
                 };
-                Some((Type::TInt(kind,Layout::default()),Some(i)))
+                Some((Type::TInt(kind,Layout::default()),Some(ConstVal::Int(i))))
+            },
+            Float(f) if f.is_finite() => {
+                Some((Type::TFloat(FDouble,Layout::new(8,8)),Some(ConstVal::Float(f))))
+            },
+            Char(CChar::Char(c)) if (c as u32)<=(::std::u8::MAX as u32) => {
+                Some((Type::TInt(IUChar,Layout::new(1,1)),Some(ConstVal::Char(c as u8))))
+            },
+            Char(CChar::Char(c)) => self.infer(&Int(::std::num::Wrapping(c as i64))),
+            Char(CChar::Raw(c)) => self.infer(&Int(::std::num::Wrapping(c as i64))),
+            Str(ref s) => {
+                let layout=Layout::new(s.len()+1,1);
+                let ty=Type::TArray(Box::new(Type::TInt(IUChar,Layout::new(1,1))),s.len()+1,layout);
+                Some((ty,Some(ConstVal::Str(s.clone()))))
             },
             _ => None,
         }
     }
     }
 
+/// Deduces the `#pragma pack(N)` and `__attribute__((aligned(N)))` of a
+/// composite from the layout clang computed, as libclang exposes neither.
+fn infer_pack_and_align(ci: &mut CompInfo) {
+    if ci.layout.align == 0 {
+        return;
+    }
+
+    let mut fields_align = 1;
+    let mut pack = ci.layout.pack;
+    for member in &ci.members {
+        let field = match *member {
+            CompMember::Field(ref f) |
+            CompMember::CompField(_, ref f) |
+            CompMember::EnumField(_, ref f) => f,
+            CompMember::Comp(ref c) => {
+                // Anonymous structs and unions are laid out in place.
+                let c = c.borrow();
+                if c.name.is_empty() {
+                    fields_align = cmp::max(fields_align, c.layout.align);
+                }
+                continue;
+            }
+            CompMember::Enum(_) => continue,
+        };
+        let align = field.ty.align();
+        fields_align = cmp::max(fields_align, align);
+        if let (None, Some(bits)) = (field.bitfields.as_ref(), field.offset) {
+            let offset = bits / 8;
+            if align > 1 && offset % align != 0 {
+                let field_pack = 1 << offset.trailing_zeros();
+                pack = Some(cmp::min(pack.unwrap_or(field_pack), field_pack));
+            }
+        }
+    }
+
+    if pack.is_none() && ci.layout.align < fields_align {
+        pack = Some(ci.layout.align);
+    }
+    let natural_align = pack.map_or(fields_align, |p| cmp::min(p, fields_align));
+    ci.layout.pack = pack;
+    if ci.layout.align > natural_align {
+        ci.layout.explicit_align = Some(ci.layout.align);
+    }
+}
+
 /// Recursively visits a cursor that represents a composite (struct or union)
 /// type and fills members with `CompMember` instances representing the fields and
 /// nested composites that make up the visited composite.
@@ -394,13 +792,49 @@ fn visit_composite(cursor: &Cursor,
                    ctx: &mut ClangParserCtx,
                    compinfo: &mut CompInfo)
                    -> CXChildVisitResult {
-    fn is_bitfield_continuation(field: &il::FieldInfo, ty: &il::Type, width: u32) -> bool {
-        match (&field.bitfields, ty) {
-            (&Some(ref bitfields), &il::TInt(_, layout)) if *ty == field.ty => {
-                bitfields.iter().map(|&(_, w)| w).fold(0u32, |acc, w| acc + w) + width <=
-                (layout.size * 8) as u32
+    /// The offset of a bitfield from the start of the storage unit of
+    /// `field`, from the bit offset computed by clang when there is one.
+    fn bitfield_offset(field: &il::FieldInfo, bit_offset: Option<usize>) -> u32 {
+        match (field.offset, bit_offset) {
+            (Some(start), Some(offset)) if offset >= start => (offset - start) as u32,
+            _ => {
+                field.bitfields
+                     .as_ref()
+                     .and_then(|bitfields| bitfields.last())
+                     .map_or(0, |last| last.offset + last.width)
             }
-            _ => false,
+        }
+    }
+
+    /// Whether a bitfield of type `ty` fits in the storage unit of `field`,
+    /// which the integer and enum bitfields of the same size share.
+    fn is_bitfield_continuation(field: &il::FieldInfo,
+                                ty: &il::Type,
+                                offset: u32,
+                                width: u32)
+                                -> bool {
+        field.bitfields.is_some() && ty.is_integral() && field.ty.is_integral() &&
+        ty.size() == field.ty.size() && offset + width <= (ty.size() * 8) as u32
+    }
+
+    /// The first bitfield of a storage unit.
+    fn new_bitfield(ctx: &mut ClangParserCtx,
+                    cursor: &Cursor,
+                    parent: &Cursor,
+                    ty: &il::Type,
+                    width: u32)
+                    -> Bitfield {
+        if !ty.is_integral() {
+            let msg = format!("Only integer and enum bitfields are supported ({}.{}).",
+                              cursor.spelling(),
+                              parent.spelling());
+            ctx.logger.warn(&msg[..]);
+        }
+        Bitfield {
+            name: cursor.spelling(),
+            ty: ty.clone(),
+            width: width,
+            offset: 0,
         }
     }
 
@@ -409,7 +843,8 @@ fn visit_composite(cursor: &Cursor,
             match *ty {
                 TComp(ref comp_ty) => return Some(comp_ty),
                 TPtr(ref ptr_ty, _, _) => ty = &**ptr_ty,
-                TArray(ref array_ty, _, _) => ty = &**array_ty,
+                TArray(ref array_ty, _, _) |
+                TFlexArray(ref array_ty) => ty = &**array_ty,
                 _ => return None,
             }
         }
@@ -420,7 +855,8 @@ fn visit_composite(cursor: &Cursor,
             match *ty {
                 TEnum(ref enum_ty) => return Some(enum_ty),
                 TPtr(ref ptr_ty, _, _) => ty = &**ptr_ty,
-                TArray(ref array_ty, _, _) => ty = &**array_ty,
+                TArray(ref array_ty, _, _) |
+                TFlexArray(ref array_ty) => ty = &**array_ty,
                 _ => return None,
             }
         }
@@ -430,34 +866,43 @@ fn visit_composite(cursor: &Cursor,
 
     match cursor.kind() {
         CXCursorKind::FieldDecl => {
-            let ty = conv_ty(ctx, &cursor.cur_type(), cursor);
+            let cursor_ty = cursor.cur_type();
+            // Only the last field can be an array of unknown size.
+            let ty = if cursor_ty.kind() == CXTypeKind::IncompleteArray {
+                TFlexArray(Box::new(conv_ty(ctx, &cursor_ty.elem_type(), cursor)))
+            } else {
+                conv_ty(ctx, &cursor_ty, cursor)
+            };
+            if ty.is_non_trivial_class() {
+                compinfo.non_trivial = true;
+            }
 
+            // Every member of a union starts at its beginning, so its
+            // bitfields never share their storage.
+            let is_union = compinfo.kind == CompKind::Union;
+            let bit_offset = cursor.offset_of_field();
             let (name, bitfields) = match (cursor.bit_width(), members.last_mut()) {
                 // The field is a continuation of an exising bitfield
-                (Some(width), Some(&mut il::CompMember::Field(ref mut field)))
-                    if is_bitfield_continuation(field, &ty, width) => {
-
-                    if let Some(ref mut bitfields) = field.bitfields {
-                        bitfields.push((cursor.spelling(), width));
-                    } else {
-                        unreachable!()
+                (Some(width), Some(&mut il::CompMember::Field(ref mut field))) |
+                (Some(width), Some(&mut il::CompMember::EnumField(_, ref mut field)))
+                    if !is_union => {
+                    let offset = bitfield_offset(field, bit_offset);
+                    if is_bitfield_continuation(field, &ty, offset, width) {
+                        if let Some(ref mut bitfields) = field.bitfields {
+                            bitfields.push(Bitfield {
+                                name: cursor.spelling(),
+                                ty: ty.clone(),
+                                width: width,
+                                offset: offset,
+                            });
+                        }
+                        return CXChildVisitResult::Continue;
                     }
-                    return CXChildVisitResult::Continue;
+                    ("".to_owned(), Some(vec![new_bitfield(ctx, cursor, parent, &ty, width)]))
                 }
                 // The field is the start of a new bitfield
                 (Some(width), _) => {
-                    // Bitfields containing enums are not supported by the c standard
-                    // https://stackoverflow.com/questions/11983231/is-it-safe-to-use-an-enum-in-a-bit-field
-                    match ty {
-                        il::TInt(_, _) => (),
-                        _ => {
-                            let msg = format!("Enums in bitfields are not supported ({}.{}).",
-                                              cursor.spelling(),
-                                              parent.spelling());
-                            ctx.logger.warn(&msg[..]);
-                        }
-                    }
-                    ("".to_owned(), Some(vec![(cursor.spelling(), width)]))
+                    ("".to_owned(), Some(vec![new_bitfield(ctx, cursor, parent, &ty, width)]))
                 }
                 // The field is not a bitfield
                 (None, _) => (cursor.spelling(), None),
@@ -514,7 +959,9 @@ fn visit_composite(cursor: &Cursor,
                 _ => false,
             };
 
-            let field = FieldInfo::new(name, ty.clone(), bitfields);
+            let mut field = FieldInfo::new(name, ty.clone(), bitfields);
+            field.comment = cursor.raw_comment();
+            field.offset = bit_offset;
             if is_composite {
                 if let Some(CompMember::Comp(c)) = members.pop() {
                     members.push(CompMember::CompField(c, field));
@@ -531,17 +978,19 @@ fn visit_composite(cursor: &Cursor,
                 members.push(CompMember::Field(field));
             }
         }
-        CXCursorKind::StructDecl | CXCursorKind::UnionDecl => {
+        CXCursorKind::StructDecl | CXCursorKind::ClassDecl | CXCursorKind::UnionDecl => {
             fwd_decl(ctx, cursor, |ctx_| {
                 // If the struct is anonymous (i.e. declared here) then it
                 // cannot be used elsewhere and so does not need to be added
                 // to globals otherwise it will be declared later and a global.
                 let decl = decl_name(ctx_, cursor);
                 let ci = decl.compinfo();
+                ci.borrow_mut().comment = cursor.raw_comment();
                 cursor.visit(|c, p| {
                     let mut ci_ = ci.borrow_mut();
                     visit_composite(c, p, ctx_, &mut ci_)
                 });
+                infer_pack_and_align(&mut ci.borrow_mut());
                 members.push(CompMember::Comp(decl.compinfo()));
             });
         }
@@ -552,6 +1001,7 @@ fn visit_composite(cursor: &Cursor,
                 // to globals otherwise it will be declared later and a global.
                 let decl = decl_name(ctx_, cursor);
                 let ci = decl.enuminfo();
+                ci.borrow_mut().comment = cursor.raw_comment();
                 cursor.visit(|c, _| {
                     let mut ci_ = ci.borrow_mut();
                     visit_enum(c, &mut ci_.items)
@@ -559,8 +1009,36 @@ fn visit_composite(cursor: &Cursor,
                 members.push(CompMember::Enum(decl.enuminfo()));
             });
         }
+        CXCursorKind::CXXBaseSpecifier => {
+            // The bases are laid out first, in declaration order.
+            let bases = members.iter().filter(|m| m.name().starts_with("_base")).count();
+            let name = if bases == 0 {
+                "_base".to_owned()
+            } else {
+                format!("_base{}", bases)
+            };
+            let ty = conv_ty(ctx, &cursor.cur_type(), cursor);
+            if ty.is_non_trivial_class() {
+                compinfo.non_trivial = true;
+            }
+            members.push(CompMember::Field(FieldInfo::new(name, ty, None)));
+        }
+        CXCursorKind::CXXMethod | CXCursorKind::Constructor | CXCursorKind::Destructor => {
+            // Copying a polymorphic class bitwise would also copy its vtable
+            // pointer.
+            if cursor.kind() == CXCursorKind::Destructor || is_copy_constructor(cursor, parent) ||
+               (cursor.kind() != CXCursorKind::Constructor && cursor.method_is_virtual()) {
+                compinfo.non_trivial = true;
+            }
+            if let Some(method) = conv_method(ctx, cursor, parent) {
+                compinfo.methods.push(method);
+            }
+        }
+        CXCursorKind::CXXAccessSpecifier => {
+            // all the members are public in Rust
+        }
         CXCursorKind::PackedAttr => {
-            compinfo.layout.packed = true;
+            compinfo.layout.pack = Some(1);
         }
         CXCursorKind::UnexposedAttr => {
             // skip unknown attributes
@@ -580,11 +1058,70 @@ fn visit_composite(cursor: &Cursor,
     CXChildVisitResult::Continue
 }
 
+/// Whether the method is a copy or move constructor of the class.
+fn is_copy_constructor(cursor: &Cursor, class: &Cursor) -> bool {
+    if cursor.kind() != CXCursorKind::Constructor {
+        return false;
+    }
+    let args = cursor.args();
+    if args.len() != 1 {
+        return false;
+    }
+    let arg_ty = args[0].cur_type();
+    match arg_ty.kind() {
+        CXTypeKind::LValueReference | CXTypeKind::RValueReference => {
+            arg_ty.pointee_type().canonical_type().declaration() == *class
+        }
+        _ => false,
+    }
+}
+
+/// Converts a C++ method, skipping the ones that may have no symbol to link
+/// to: the virtual methods and the ones defined in the class body.
+fn conv_method(ctx: &mut ClangParserCtx, cursor: &Cursor, parent: &Cursor) -> Option<MethodInfo> {
+    let skipped = if cursor.method_is_virtual() {
+        Some("virtual")
+    } else if cursor.definition() == *cursor {
+        Some("inline")
+    } else {
+        None
+    };
+    if let Some(why) = skipped {
+        let msg = format!("Skipping the {} method `{}::{}` ({}).",
+                          why,
+                          parent.spelling(),
+                          cursor.spelling(),
+                          cursor.location());
+        ctx.logger.warn(&msg[..]);
+        return None;
+    }
+
+    let mangled_name = match cxx_mangling(cursor) {
+        Some(mangled_name) => mangled_name,
+        None => return None,
+    };
+    let kind = match cursor.kind() {
+        CXCursorKind::Constructor => MethodKind::Constructor,
+        CXCursorKind::Destructor => MethodKind::Destructor,
+        _ if cursor.method_is_static() => MethodKind::Static,
+        _ => MethodKind::Normal,
+    };
+    Some(MethodInfo {
+        name: cursor.spelling(),
+        kind: kind,
+        is_const: cursor.method_is_const(),
+        mangled_name: mangled_name,
+        sig: mk_fn_sig(ctx, &cursor.cur_type(), cursor),
+        comment: cursor.raw_comment(),
+    })
+}
+
 fn visit_enum(cursor: &Cursor, items: &mut Vec<EnumItem>) -> CXChildVisitResult {
     if cursor.kind() == CXCursorKind::EnumConstantDecl {
         let name = cursor.spelling();
         let val = cursor.enum_val();
-        let item = EnumItem::new(name, val);
+        let mut item = EnumItem::new(name, val);
+        item.comment = cursor.raw_comment();
         items.push(item);
     }
     CXChildVisitResult::Continue
@@ -628,15 +1165,19 @@ fn visit_top(cursor: &Cursor,
     }
 
     match cursor.kind() {
-        CXCursorKind::UnexposedDecl => CXChildVisitResult::Recurse,
-        CXCursorKind::StructDecl | CXCursorKind::UnionDecl => {
+        CXCursorKind::UnexposedDecl |
+        CXCursorKind::LinkageSpec |
+        CXCursorKind::Namespace => CXChildVisitResult::Recurse,
+        CXCursorKind::StructDecl | CXCursorKind::ClassDecl | CXCursorKind::UnionDecl => {
             fwd_decl(ctx, cursor, |ctx_| {
                 let decl = decl_name(ctx_, cursor);
                 let ci = decl.compinfo();
+                ci.borrow_mut().comment = cursor.raw_comment();
                 cursor.visit(|c, p| {
                     let mut ci_ = ci.borrow_mut();
                     visit_composite(c, p, ctx_, &mut ci_)
                 });
+                infer_pack_and_align(&mut ci.borrow_mut());
                 ctx_.globals.push(GComp(ci));
             });
             CXChildVisitResult::Continue
@@ -645,6 +1186,7 @@ fn visit_top(cursor: &Cursor,
             fwd_decl(ctx, cursor, |ctx_| {
                 let decl = decl_name(ctx_, cursor);
                 let ei = decl.enuminfo();
+                ei.borrow_mut().comment = cursor.raw_comment();
                 cursor.visit(|c, _: &Cursor| {
                     let mut ei_ = ei.borrow_mut();
                     visit_enum(c, &mut ei_.items)
@@ -655,7 +1197,18 @@ fn visit_top(cursor: &Cursor,
         }
         CXCursorKind::FunctionDecl => {
             let linkage = cursor.linkage();
-            if linkage != CXLinkageKind::External && linkage != CXLinkageKind::UniqueExternal {
+            let is_static = linkage == CXLinkageKind::Internal;
+            if linkage != CXLinkageKind::External && linkage != CXLinkageKind::UniqueExternal &&
+               !(is_static && ctx.options.wrap_static_fns) {
+                return CXChildVisitResult::Continue;
+            }
+
+            let ty = cursor.cur_type();
+            if is_static && ty.is_variadic() {
+                let msg = format!("Can't wrap the variadic static function `{}` ({}).",
+                                  cursor.spelling(),
+                                  cursor.location());
+                ctx.logger.warn(&msg[..]);
                 return CXChildVisitResult::Continue;
             }
 
@@ -663,10 +1216,19 @@ fn visit_top(cursor: &Cursor,
             let vi = func.varinfo();
             let mut vi = vi.borrow_mut();
 
-            let ty = cursor.cur_type();
             let layout = Layout::new(ty.size(), ty.align());
 
             vi.ty = TFuncPtr(mk_fn_sig(ctx, &ty, cursor), layout);
+            vi.comment = cursor.raw_comment();
+            vi.mangled_name = cxx_mangling(cursor);
+            if is_static {
+                let (file, _, _, _) = cursor.location().location();
+                vi.static_fn = Some(StaticFnInfo {
+                    header: file.name().unwrap_or_else(String::new),
+                    ret_ty: ty.ret_type().name(),
+                    arg_tys: cursor.args().iter().map(|arg| arg.cur_type().name()).collect(),
+                });
+            }
             ctx.globals.push(func);
 
             CXChildVisitResult::Continue
@@ -683,8 +1245,10 @@ fn visit_top(cursor: &Cursor,
             let mut vi = vi.borrow_mut();
             vi.ty = ty.clone();
             vi.is_const = cursor.cur_type().is_const();
+            vi.comment = cursor.raw_comment();
+            vi.mangled_name = cxx_mangling(cursor);
             cursor.visit(|c, _: &Cursor| {
-                vi.val = visit_literal(c, unit);
+                vi.val = visit_literal(c, unit).map(ConstVal::Int);
                 CXChildVisitResult::Continue
             });
             ctx.globals.push(var);
@@ -702,6 +1266,7 @@ fn visit_top(cursor: &Cursor,
             let ti = typedef.typeinfo();
             let mut ti = ti.borrow_mut();
             ti.ty = ty.clone();
+            ti.comment = cursor.raw_comment();
             ctx.globals.push(typedef);
 
             opaque_ty(ctx, &under_ty);
@@ -717,6 +1282,18 @@ fn visit_top(cursor: &Cursor,
                     None
                 }
             ).collect();
+            if ctx.options.fn_macros != FnMacroStyle::Skip && cursor.is_macro_function_like() {
+                match conv_fn_macro(ctx, &tokens) {
+                    Some(mi) => ctx.globals.push(GMacro(Rc::new(RefCell::new(mi)))),
+                    None => {
+                        let msg = format!("Can't convert the function-like macro `{}` ({}).",
+                                          cursor.spelling(),
+                                          cursor.location());
+                        ctx.logger.warn(&msg[..]);
+                    }
+                }
+                return CXChildVisitResult::Continue;
+            }
             if let nom::IResult::Done(_,(id,val)) =
                     expr::IdentifierParser::new(&ctx.defined_macros).macro_definition(&tokens) {
                 let id=id.to_owned();
@@ -727,6 +1304,11 @@ fn visit_top(cursor: &Cursor,
                         ty:ty,
                         val:val,
                         is_const:true,
+                        is_macro:true,
+                        comment:String::new(),
+                        static_fn:None,
+                        namespace:vec![],
+                        mangled_name:None,
                     })));
                     ctx.globals.push(var);
                 }
@@ -739,6 +1321,129 @@ fn visit_top(cursor: &Cursor,
     }
 }
 
+/// Converts a function-like macro whose body is made of its parameters,
+/// numbers, other macros and arithmetic operators. The body must be understood
+/// by cexpr once the parameters are replaced by `1`.
+fn conv_fn_macro(ctx: &ClangParserCtx, tokens: &[CexprToken]) -> Option<MacroInfo> {
+    use cexpr::expr::{EvalResult, IdentifierParser};
+    use cexpr::nom::IResult;
+
+    fn text(token: &CexprToken) -> Option<String> {
+        String::from_utf8(token.raw.to_vec()).ok()
+    }
+
+    let name = match tokens.first().and_then(text) {
+        Some(name) => name,
+        None => return None,
+    };
+    if tokens.get(1).map_or(true, |t| &*t.raw != b"(") {
+        return None;
+    }
+    let mut params = vec![];
+    let mut i = 2;
+    if tokens.get(i).map_or(false, |t| &*t.raw == b")") {
+        i += 1;
+    } else {
+        loop {
+            match tokens.get(i).map(|t| (&t.kind, text(t))) {
+                Some((&CexprTokenKind::Identifier, Some(param))) => params.push(param),
+                _ => return None,
+            }
+            i += 1;
+            match tokens.get(i).map(|t| &*t.raw) {
+                Some(b",") => i += 1,
+                Some(b")") => {
+                    i += 1;
+                    break;
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    let allow_floats = ctx.options.fn_macros == FnMacroStyle::MacroRules;
+    let parser = IdentifierParser::new(&ctx.defined_macros);
+    let is_param = |token: &CexprToken| {
+        match token.kind {
+            CexprTokenKind::Identifier => text(token).map_or(false, |t| params.contains(&t)),
+            _ => false,
+        }
+    };
+    let substituted: Vec<_> = tokens[i..]
+                                  .iter()
+                                  .map(|token| {
+                                      if is_param(token) {
+                                          CexprToken {
+                                              kind: CexprTokenKind::Literal,
+                                              raw: b"1".to_vec().into_boxed_slice(),
+                                          }
+                                      } else {
+                                          token.clone()
+                                      }
+                                  })
+                                  .collect();
+    let rest_len = match parser.expr(&substituted) {
+        IResult::Done(rest, EvalResult::Int(_)) => rest.len(),
+        IResult::Done(rest, EvalResult::Float(_)) if allow_floats => rest.len(),
+        _ => return None,
+    };
+    // libclang may append the token following the macro to its extent.
+    if rest_len > 1 || rest_len == substituted.len() {
+        return None;
+    }
+
+    let mut body = vec![];
+    let end = tokens.len() - rest_len;
+    for (start, token) in tokens.iter().enumerate().take(end).skip(i) {
+        let spelling = match text(token) {
+            Some(spelling) => spelling,
+            None => return None,
+        };
+        body.push(match token.kind {
+            CexprTokenKind::Identifier => {
+                match params.iter().position(|p| *p == spelling) {
+                    Some(n) => MacroToken::Param(n),
+                    None => {
+                        // The `macro_rules!` macros expand where the
+                        // constants may not be in scope, so they get their
+                        // values.
+                        match ctx.defined_macros.get(&*token.raw) {
+                            Some(&EvalResult::Int(::std::num::Wrapping(v))) if allow_floats => {
+                                MacroToken::Int(v)
+                            }
+                            Some(&EvalResult::Int(_)) => MacroToken::Const(spelling),
+                            Some(&EvalResult::Float(f)) if allow_floats => MacroToken::Float(f),
+                            _ => return None,
+                        }
+                    }
+                }
+            }
+            CexprTokenKind::Literal => {
+                match parser.expr(&tokens[start..start + 1]) {
+                    IResult::Done(_, EvalResult::Int(::std::num::Wrapping(v))) => MacroToken::Int(v),
+                    IResult::Done(_, EvalResult::Float(f)) if allow_floats => MacroToken::Float(f),
+                    _ => return None,
+                }
+            }
+            CexprTokenKind::Punctuation => {
+                match &spelling[..] {
+                    "+" | "-" | "*" | "/" | "%" | "<<" | ">>" | "&" | "|" | "^" | "(" | ")" => {
+                        MacroToken::Punct(spelling)
+                    }
+                    "~" => MacroToken::Punct("!".to_owned()),
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        });
+    }
+    Some(MacroInfo {
+        name: name,
+        params: params,
+        body: body,
+    })
+}
+
 fn log_err_warn(ctx: &mut ClangParserCtx, msg: &str, is_err: bool) {
     if is_err {
         ctx.err_count += 1;
@@ -757,6 +1462,8 @@ pub fn parse(options: ClangParserOptions, logger: &Logger) -> Result<Vec<Global>
         defined_macros: HashMap::new(),
         logger: logger,
         err_count: 0,
+        int128_comps: HashMap::new(),
+        unsupported_comps: HashMap::new(),
     };
 
     let ix = cx::Index::create(false, false);
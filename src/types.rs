@@ -24,6 +24,12 @@ pub enum Global {
     GVar(Rc<RefCell<VarInfo>>),
     /// A function prototype, like `int func();`.
     GFunc(Rc<RefCell<VarInfo>>),
+    /// A function-like macro, like `#define SQUARE(x) ((x) * (x))`.
+    GMacro(Rc<RefCell<MacroInfo>>),
+    /// Macros sharing a prefix, grouped into a newtype of the integer type
+    /// `ty` with the names of the constants (minus the prefix) and their
+    /// values.
+    GMacroGroup(Rc<RefCell<TypeInfo>>, Vec<(String, i64)>),
     /// Something else.
     GOther,
 }
@@ -64,13 +70,15 @@ impl Global {
 impl fmt::Debug for Global {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            GType(ref ti) => ti.borrow().fmt(f),
+            GType(ref ti) |
+            GMacroGroup(ref ti, _) => ti.borrow().fmt(f),
             GComp(ref ci) |
             GCompDecl(ref ci) => ci.borrow().fmt(f),
             GEnum(ref ei) |
             GEnumDecl(ref ei) => ei.borrow().fmt(f),
             GVar(ref vi) |
             GFunc(ref vi) => vi.borrow().fmt(f),
+            GMacro(ref mi) => mi.borrow().fmt(f),
             GOther => "*".fmt(f),
         }
     }
@@ -91,6 +99,9 @@ pub struct FuncSig {
     pub abi: abi::Abi,
 }
 
+/// The prefix of the opaque structs standing for the unsupported types.
+pub const UNSUPPORTED_PREFIX: &'static str = "__BindgenUnsupported";
+
 /// A representation of a C type.
 #[derive(Clone, PartialEq, Debug)]
 pub enum Type {
@@ -100,9 +111,20 @@ pub enum Type {
     TInt(IKind, Layout),
     /// A C floating-point number, like `float` or `double`.
     TFloat(FKind, Layout),
+    /// A C complex number of the floating-point kind, like `double _Complex`,
+    /// with the layout of the real and imaginary parts together.
+    TComplex(FKind, Layout),
     /// A Pointer, the boolean indicating if it is const.
     TPtr(Box<Type>, bool, Layout),
     TArray(Box<Type>, usize, Layout),
+    /// A SIMD vector of the element type, with the layout of the whole vector.
+    TVector(Box<Type>, usize, Layout),
+    /// A flexible array member of the element type, taking no room in the
+    /// struct.
+    TFlexArray(Box<Type>),
+    /// A C11 `_Atomic` integer or pointer of the value type, with the layout
+    /// of the atomic.
+    TAtomic(Box<Type>, Layout),
     TFuncProto(FuncSig, Layout),
     TFuncPtr(FuncSig, Layout),
     /// A typedef declaration?
@@ -119,14 +141,17 @@ impl Type {
         match *self {
             TInt(_, l) |
             TFloat(_, l) |
+            TComplex(_, l) |
             TFuncProto(_, l) |
             TFuncPtr(_, l) |
-            TPtr(_, _, l) => l.size,
+            TPtr(_, _, l) |
+            TVector(_, _, l) |
+            TAtomic(_, l) => l.size,
             TArray(_, size, l) => l.size * size,
             TNamed(ref ti) => ti.borrow().layout.size,
             TComp(ref ci) => ci.borrow().layout.size,
             TEnum(ref ei) => ei.borrow().layout.size,
-            TVoid => 0,
+            TVoid | TFlexArray(_) => 0,
         }
     }
 
@@ -136,13 +161,17 @@ impl Type {
         match *self {
             TInt(_, l) |
             TFloat(_, l) |
+            TComplex(_, l) |
             TFuncProto(_, l) |
             TFuncPtr(_, l) |
             TPtr(_, _, l) |
-            TArray(_, _, l) => l.align,
+            TArray(_, _, l) |
+            TVector(_, _, l) |
+            TAtomic(_, l) => l.align,
             TNamed(ref ti) => ti.borrow().layout.align,
             TComp(ref ci) => ci.borrow().layout.align,
             TEnum(ref ei) => ei.borrow().layout.align,
+            TFlexArray(ref t) => t.align(),
             TVoid => 0,
         }
     }
@@ -154,10 +183,21 @@ impl Type {
     /// Whether the type contains a field can't be derived
     pub fn can_auto_derive(&self) -> bool {
         match *self {
-            TArray(_, size, _) => size <= 32,
+            TArray(_, size, _) |
+            TVector(_, size, _) => size <= 32,
+            // The opaque blobs only derive `Copy`.
+            TFloat(FLongDouble, _) |
+            TFloat(FFloat128, _) |
+            TComplex(FLongDouble, _) |
+            TComplex(FFloat128, _) => false,
+            TNamed(ref ti) => {
+                let ti = ti.borrow();
+                !ti.opaque && ti.ty.can_auto_derive()
+            }
             TComp(ref comp) => {
-                comp.borrow()
-                    .members
+                let comp = comp.borrow();
+                !comp.opaque && comp.layout.is_representable() &&
+                comp.members
                     .iter()
                     .all(|member| {
                         match *member {
@@ -170,6 +210,97 @@ impl Type {
             _ => true,
         }
     }
+
+    /// Whether the type is a float without a Rust equivalent, or a complex
+    /// number of them, generated as a blob which can't be passed by value like
+    /// the C type.
+    pub fn is_blob_float(&self) -> bool {
+        match *self {
+            TFloat(FLongDouble, _) |
+            TFloat(FFloat128, _) |
+            TComplex(FLongDouble, _) |
+            TComplex(FFloat128, _) => true,
+            TNamed(ref ti) => ti.borrow().ty.is_blob_float(),
+            _ => false,
+        }
+    }
+
+    /// Whether the type is an integer or an enum, behind the typedefs.
+    pub fn is_integral(&self) -> bool {
+        match *self {
+            TInt(..) | TEnum(..) => true,
+            TNamed(ref ti) => ti.borrow().ty.is_integral(),
+            _ => false,
+        }
+    }
+
+    /// Whether the type is one bindgen doesn't support, replaced by an opaque
+    /// struct which can't be passed by value like the C type.
+    pub fn is_unsupported(&self) -> bool {
+        match *self {
+            TComp(ref ci) => ci.borrow().name.starts_with(UNSUPPORTED_PREFIX),
+            TNamed(ref ti) => ti.borrow().ty.is_unsupported(),
+            _ => false,
+        }
+    }
+
+    /// The integer or pointer type behind the typedefs of the type, which can
+    /// be the value of a Rust atomic.
+    pub fn atomic_value(&self) -> Option<Type> {
+        match *self {
+            TInt(I128, _) |
+            TInt(IU128, _) => None,
+            TPtr(ref t, _, _) if t.is_fn_proto() => None,
+            TInt(..) | TPtr(..) => Some(self.clone()),
+            TNamed(ref ti) => ti.borrow().ty.atomic_value(),
+            _ => None,
+        }
+    }
+
+    fn is_fn_proto(&self) -> bool {
+        match *self {
+            TFuncProto(..) => true,
+            TNamed(ref ti) => ti.borrow().ty.is_fn_proto(),
+            _ => false,
+        }
+    }
+
+    /// Whether the type is a C++ class, or an array of them, that can't be
+    /// passed by value like a C struct.
+    pub fn is_non_trivial_class(&self) -> bool {
+        match *self {
+            TArray(ref t, _, _) => t.is_non_trivial_class(),
+            TNamed(ref ti) => ti.borrow().ty.is_non_trivial_class(),
+            TComp(ref ci) => ci.borrow().non_trivial,
+            _ => false,
+        }
+    }
+
+    /// Whether the type is `Copy` once converted to Rust, which is required
+    /// for it to be the field of a Rust `union` or of a struct deriving
+    /// `Copy`.
+    pub fn is_copy(&self) -> bool {
+        match *self {
+            TAtomic(..) |
+            TFlexArray(_) => false,
+            TArray(ref t, _, _) => t.is_copy(),
+            TNamed(ref ti) => ti.borrow().ty.is_copy(),
+            TComp(ref comp) => {
+                let comp = comp.borrow();
+                !comp.non_trivial &&
+                comp.members
+                    .iter()
+                    .all(|member| {
+                        match *member {
+                            CompMember::Field(ref f) |
+                            CompMember::CompField(_, ref f) => f.ty.is_copy(),
+                            _ => true,
+                        }
+                    })
+            }
+            _ => true,
+        }
+    }
 }
 
 /// Describes the layout of an element
@@ -178,8 +309,12 @@ pub struct Layout {
     /// The size in bytes of the element.
     pub size: usize,
     pub align: usize,
-    /// See `#[repr(C, Packed)]`.
-    pub packed: bool,
+    /// The maximum alignment of the fields, set by `#pragma pack(N)` or
+    /// `__attribute__((packed))` (1). See `#[repr(C, packed(N))]`.
+    pub pack: Option<usize>,
+    /// The alignment forced by `__attribute__((aligned(N)))`. See
+    /// `#[repr(C, align(N))]`.
+    pub explicit_align: Option<usize>,
 }
 
 impl Layout {
@@ -187,9 +322,15 @@ impl Layout {
         Layout {
             size: size,
             align: align,
-            packed: false,
+            pack: None,
+            explicit_align: None,
         }
     }
+
+    /// Whether Rust can express both the packing and the alignment.
+    pub fn is_representable(&self) -> bool {
+        self.pack.is_none() || self.explicit_align.is_none()
+    }
 }
 
 impl Default for Layout {
@@ -197,7 +338,8 @@ impl Default for Layout {
         Layout {
             size: 0,
             align: 0,
-            packed: false,
+            pack: None,
+            explicit_align: None,
         }
     }
 }
@@ -219,6 +361,10 @@ pub enum IKind {
     ILongLong,
     IULongLong,
     IWChar,
+    /// `__int128`, see `Builder::native_int128`.
+    I128,
+    /// `unsigned __int128`, see `Builder::native_int128`.
+    IU128,
 }
 
 impl IKind {
@@ -236,6 +382,8 @@ impl IKind {
             ILongLong => true,
             IULongLong => false,
             IWChar => false,
+            I128 => true,
+            IU128 => false,
         }
     }
 }
@@ -244,6 +392,10 @@ impl IKind {
 pub enum FKind {
     FFloat,
     FDouble,
+    /// A `long double` wider than a `double`, generated as a blob.
+    FLongDouble,
+    /// A `__float128` or `_Float128`, generated as a blob.
+    FFloat128,
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -291,6 +443,19 @@ pub struct CompInfo {
     pub name: String,
     pub members: Vec<CompMember>,
     pub layout: Layout,
+    /// The documentation comment, empty if there is none.
+    pub comment: String,
+    /// The C++ namespaces the type is declared in, outermost first.
+    pub namespace: Vec<String>,
+    /// The non-virtual C++ methods, constructors and destructor.
+    pub methods: Vec<MethodInfo>,
+    /// Is the type generated as a blob hiding its fields?
+    pub opaque: bool,
+    /// Does the C++ class, or one of its bases or fields, declare a
+    /// destructor, a copy or move constructor or a virtual method? It then
+    /// can't be copied bitwise, and the C++ ABI passes it by value through a
+    /// hidden pointer.
+    pub non_trivial: bool,
 }
 
 impl CompInfo {
@@ -300,6 +465,11 @@ impl CompInfo {
             name: name,
             members: members,
             layout: layout,
+            comment: String::new(),
+            namespace: vec![],
+            methods: vec![],
+            opaque: false,
+            non_trivial: false,
         }
     }
 }
@@ -310,20 +480,60 @@ impl fmt::Debug for CompInfo {
     }
 }
 
+/// The kind of a C++ method.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MethodKind {
+    Normal,
+    Static,
+    Constructor,
+    Destructor,
+}
+
+/// A non-virtual C++ method.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MethodInfo {
+    pub name: String,
+    pub kind: MethodKind,
+    /// Is `this` const?
+    pub is_const: bool,
+    /// The mangled symbol name.
+    pub mangled_name: String,
+    /// The signature, without the implicit `this` argument.
+    pub sig: FuncSig,
+    /// The documentation comment, empty if there is none.
+    pub comment: String,
+}
+
+/// A bitfield, stored with its neighbours in the storage unit of a field.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Bitfield {
+    pub name: String,
+    pub ty: Type,
+    pub width: u32,
+    /// The offset in bits from the start of the storage unit.
+    pub offset: u32,
+}
+
 /// A field in a struct or a union.
 #[derive(Clone, PartialEq, Debug)]
 pub struct FieldInfo {
     pub name: String,
     pub ty: Type,
-    pub bitfields: Option<Vec<(String, u32)>>,
+    pub bitfields: Option<Vec<Bitfield>>,
+    /// The offset of the field in bits, as computed by clang.
+    pub offset: Option<usize>,
+    /// The documentation comment, empty if there is none.
+    pub comment: String,
 }
 
 impl FieldInfo {
-    pub fn new(name: String, ty: Type, bitfields: Option<Vec<(String, u32)>>) -> FieldInfo {
+    pub fn new(name: String, ty: Type, bitfields: Option<Vec<Bitfield>>) -> FieldInfo {
         FieldInfo {
             name: name,
             ty: ty,
             bitfields: bitfields,
+            offset: None,
+            comment: String::new(),
         }
     }
 }
@@ -336,6 +546,27 @@ pub struct EnumInfo {
     /// The underlining representation of the enum.
     pub kind: IKind,
     pub layout: Layout,
+    /// The documentation comment, empty if there is none.
+    pub comment: String,
+    /// The C++ namespaces the enum is declared in, outermost first.
+    pub namespace: Vec<String>,
+    /// The style chosen by regex, if any.
+    pub style: Option<EnumStyle>,
+}
+
+/// How an enum is generated.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EnumStyle {
+    /// A Rust `enum`.
+    Rust,
+    /// A type alias and constants of that type.
+    Consts,
+    /// A newtype with associated constants, see `Builder::newtype_enum`.
+    Newtype,
+    /// Constants inside a module, see `Builder::constified_enum_module`.
+    ModuleConsts,
+    /// A newtype with bitwise operators, see `Builder::bitfield_enum`.
+    Bitfield,
 }
 
 impl EnumInfo {
@@ -345,6 +576,27 @@ impl EnumInfo {
             items: items,
             kind: kind,
             layout: layout,
+            comment: String::new(),
+            namespace: vec![],
+            style: None,
+        }
+    }
+
+    /// The style of the enum, which defaults to a Rust `enum` or to
+    /// constants depending on `rust_enums`.
+    pub fn effective_style(&self, rust_enums: bool) -> EnumStyle {
+        match self.style {
+            Some(style) => style,
+            None if rust_enums => EnumStyle::Rust,
+            None => EnumStyle::Consts,
+        }
+    }
+
+    /// Whether the values of the enum are plain integers of its type.
+    pub fn has_int_values(&self, rust_enums: bool) -> bool {
+        match self.effective_style(rust_enums) {
+            EnumStyle::Consts | EnumStyle::ModuleConsts => true,
+            _ => false,
         }
     }
 }
@@ -360,6 +612,8 @@ impl fmt::Debug for EnumInfo {
 pub struct EnumItem {
     pub name: String,
     pub val: i64,
+    /// The documentation comment, empty if there is none.
+    pub comment: String,
 }
 
 impl EnumItem {
@@ -367,6 +621,7 @@ impl EnumItem {
         EnumItem {
             name: name,
             val: val,
+            comment: String::new(),
         }
     }
 }
@@ -376,6 +631,12 @@ pub struct TypeInfo {
     pub name: String,
     pub ty: Type,
     pub layout: Layout,
+    /// The documentation comment, empty if there is none.
+    pub comment: String,
+    /// The C++ namespaces the typedef is declared in, outermost first.
+    pub namespace: Vec<String>,
+    /// Is the typedef generated as a blob instead of an alias?
+    pub opaque: bool,
 }
 
 impl TypeInfo {
@@ -384,6 +645,9 @@ impl TypeInfo {
             name: name,
             ty: ty,
             layout: layout,
+            comment: String::new(),
+            namespace: vec![],
+            opaque: false,
         }
     }
 }
@@ -399,10 +663,19 @@ impl fmt::Debug for TypeInfo {
 pub struct VarInfo {
     pub name: String,
     pub ty: Type,
-    // TODO: support non-integer constants
-    pub val: Option<i64>,
+    pub val: Option<ConstVal>,
     /// Is the variable constant?
     pub is_const: bool,
+    /// Is it an object-like macro?
+    pub is_macro: bool,
+    /// The documentation comment, empty if there is none.
+    pub comment: String,
+    /// Set for the `static` functions, which are called through a C wrapper.
+    pub static_fn: Option<StaticFnInfo>,
+    /// The C++ namespaces the variable is declared in, outermost first.
+    pub namespace: Vec<String>,
+    /// The mangled symbol name of C++ variables and functions.
+    pub mangled_name: Option<String>,
 }
 
 impl VarInfo {
@@ -412,10 +685,58 @@ impl VarInfo {
             ty: ty,
             val: None,
             is_const: false,
+            is_macro: false,
+            comment: String::new(),
+            static_fn: None,
+            namespace: vec![],
+            mangled_name: None,
         }
     }
 }
 
+/// The value of a constant.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ConstVal {
+    Int(i64),
+    Float(f64),
+    /// A narrow character literal, like `'x'`.
+    Char(u8),
+    /// A string literal, without its terminating nul byte.
+    Str(Vec<u8>),
+}
+
+/// A function-like macro whose body is an arithmetic expression.
+#[derive(Clone, Debug)]
+pub struct MacroInfo {
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: Vec<MacroToken>,
+}
+
+/// A token of the body of a function-like macro.
+#[derive(Clone, PartialEq, Debug)]
+pub enum MacroToken {
+    /// The parameter of the given index.
+    Param(usize),
+    /// An object-like macro, converted to a constant.
+    Const(String),
+    Int(i64),
+    Float(f64),
+    /// An operator or a parenthesis, spelled as in Rust.
+    Punct(String),
+}
+
+/// What is needed to write the C wrapper of a `static` function.
+#[derive(Clone, Debug)]
+pub struct StaticFnInfo {
+    /// The header defining the function.
+    pub header: String,
+    /// The C spelling of the return type.
+    pub ret_ty: String,
+    /// The C spelling of the argument types.
+    pub arg_tys: Vec<String>,
+}
+
 impl fmt::Debug for VarInfo {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.name.fmt(f)
@@ -0,0 +1,21 @@
+struct inner {
+    int x;
+};
+
+struct outer {
+    struct inner *inner;
+};
+
+typedef struct outer outer_t;
+
+struct unrelated {
+    int y;
+};
+
+struct blocked {
+    int z;
+};
+
+int allowed_fn(outer_t *o);
+int other_fn(struct unrelated *u);
+void blocked_fn(struct blocked *b);
@@ -0,0 +1,23 @@
+namespace outer {
+namespace inner {
+
+class Point {
+public:
+    Point(int x, int y);
+    ~Point();
+    int x() const;
+    void translate(int dx, int dy);
+    static Point origin();
+
+private:
+    int x_;
+    int y_;
+};
+
+}
+
+typedef inner::Point point_t;
+
+void draw(const inner::Point *p);
+
+}
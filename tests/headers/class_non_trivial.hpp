@@ -0,0 +1,24 @@
+class Trivial {
+public:
+    int value;
+};
+
+class Handle {
+public:
+    Handle(const Handle &other);
+    int fd;
+};
+
+class Holder {
+public:
+    Handle handle;
+};
+
+class Shape {
+public:
+    virtual int area();
+    int sides;
+};
+
+Trivial make_trivial();
+Handle open_handle(int fd);
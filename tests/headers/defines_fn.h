@@ -0,0 +1,5 @@
+#define SHIFT 8
+#define MAKE_VERSION(a, b, c) (((a) << 16) | ((b) << SHIFT) | (c))
+#define INVERT(x) (~(x))
+#define MIN(a, b) (((a) < (b)) ? (a) : (b))
+#define HALF(x) ((x) * 0.5)
@@ -0,0 +1,6 @@
+#define LIB_OPEN_READ 0x1
+#define LIB_OPEN_WRITE 0x2
+#define LIB_OPEN_CREATE 0x40
+#define LIB_VERSION 3
+
+int lib_open(const char *path, unsigned int flags);
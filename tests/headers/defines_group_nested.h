@@ -0,0 +1,4 @@
+#define LIB_OPEN_READ 1
+#define LIB_OPEN_WRITE 2
+#define LIB_OPEN_EX_SYNC 4
+#define LIB_OPEN_EX_DIRECT 8
@@ -0,0 +1,4 @@
+#define VERSION "1.2"
+#define PI 3.14159
+#define NEG_HALF -0.5
+#define SEP 'x'
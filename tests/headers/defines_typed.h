@@ -0,0 +1,6 @@
+typedef unsigned int open_flags_t;
+
+#define OPEN_FLAGS_READ 0x1
+#define OPEN_FLAGS_WRITE 0x2
+#define MODE_FAST 3
+#define OTHER 4
@@ -0,0 +1 @@
+#define BIG_OFFSET 4294967296
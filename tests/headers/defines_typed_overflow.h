@@ -0,0 +1,2 @@
+#define SMALL_OK 0x10
+#define SMALL_BIG 0x1FF
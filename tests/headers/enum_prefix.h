@@ -0,0 +1,9 @@
+enum color {
+    COLOR_RED,
+    COLOR_DARK_GREEN,
+};
+
+enum mode {
+    MODE_1X,
+    MODE_FAST,
+};
@@ -0,0 +1,9 @@
+enum color {
+    COLOR_NONE,
+    COLOR_RED,
+};
+
+enum shape {
+    SHAPE_NONE,
+    SHAPE_SQUARE,
+};
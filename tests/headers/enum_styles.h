@@ -0,0 +1,16 @@
+enum Foo {
+    Bar = 0,
+    Qux
+};
+
+enum Neg {
+    MinusOne = -1,
+    One = 1,
+};
+
+enum Flags {
+    Read = 1,
+    Write = 2,
+};
+
+void set_neg(enum Neg n);
@@ -0,0 +1,3 @@
+static inline int add(int a, int b) { return a + b; }
+static inline void reset(int *p) { *p = 0; }
+int exported(void);
@@ -0,0 +1 @@
+void fill(int n, double buf[n]);
@@ -0,0 +1,2 @@
+void fill_grid(int n, int m, double grid[n][m]);
+void fill_rows(int m, double (*rows)[m]);
@@ -0,0 +1,11 @@
+struct point_t {
+    int x_pos;
+};
+
+enum color_t {
+    COLOR_RED,
+};
+
+extern int GlobalCount;
+
+int GetColor(struct point_t *p);
@@ -0,0 +1,19 @@
+typedef unsigned int counter_t;
+
+struct Pair {
+    int a;
+    int b;
+};
+
+struct Shared {
+    _Atomic int state;
+    _Atomic unsigned long long total;
+    _Atomic(counter_t) hits;
+    _Atomic(struct Shared *) next;
+    _Atomic _Bool ready;
+    _Atomic struct Pair pair;
+    _Atomic(counter_t *) pending;
+    _Atomic(int **) slots;
+};
+
+void publish(_Atomic struct Pair pair);
@@ -0,0 +1,6 @@
+typedef _Atomic int atomic_count_t;
+
+struct Flags {
+    _Atomic char flag;
+    atomic_count_t count;
+};
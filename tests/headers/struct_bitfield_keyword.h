@@ -0,0 +1,4 @@
+struct flags {
+    unsigned type : 3;
+    unsigned mode : 5;
+};
@@ -0,0 +1,8 @@
+struct Signal {
+    float _Complex gain;
+    double _Complex samples[4];
+};
+
+double _Complex dot(const double _Complex *x, const double _Complex *y, int n);
+
+extern float _Complex unit;
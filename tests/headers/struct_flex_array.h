@@ -0,0 +1,4 @@
+struct msg {
+    unsigned int len;
+    char data[];
+};
@@ -0,0 +1,5 @@
+struct with_int128 {
+    __int128 a;
+    __int128 b;
+    unsigned __int128 c;
+};
@@ -0,0 +1,6 @@
+struct with_long_double {
+    char c;
+    long double ld;
+};
+
+long double scale(long double x);
@@ -0,0 +1,15 @@
+struct inner {
+    int a;
+    double b;
+};
+
+struct outer {
+    struct inner i;
+    char c;
+};
+
+typedef struct {
+    int x;
+} hidden_t;
+
+void use_hidden(hidden_t h);
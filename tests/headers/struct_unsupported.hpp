@@ -0,0 +1,9 @@
+struct Target {
+    int a;
+};
+
+struct WithUnsupported {
+    char tag;
+    int Target::*member;
+    int after;
+};
@@ -0,0 +1,14 @@
+/**
+ * A point in the plane.
+ */
+struct point {
+    /// The horizontal coordinate.
+    int x;
+    int y; /**< The vertical coordinate. */
+};
+
+/**
+ * \brief Moves a point.
+ * \param p The point to move.
+ */
+void move_point(struct point *p);
@@ -0,0 +1,10 @@
+struct field_aligned {
+    char a;
+    int b __attribute__((aligned(16)));
+};
+
+struct bitfield_shared {
+    unsigned a: 4;
+    unsigned b: 4;
+    char c;
+};
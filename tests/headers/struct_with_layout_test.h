@@ -0,0 +1,5 @@
+struct foo {
+    char a;
+    int b;
+    unsigned c : 4;
+};
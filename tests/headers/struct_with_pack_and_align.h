@@ -0,0 +1,15 @@
+#pragma pack(push, 2)
+struct packed2 {
+    char a;
+    int b;
+};
+#pragma pack(pop)
+
+struct cache_line {
+    int counter;
+} __attribute__((aligned(64)));
+
+struct packed_aligned {
+    char a;
+    int b;
+} __attribute__((packed, aligned(4)));
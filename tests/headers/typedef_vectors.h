@@ -0,0 +1,3 @@
+typedef int v4si __attribute__((vector_size(16)));
+typedef float v4sf __attribute__((vector_size(16)));
+typedef short v2hi __attribute__((vector_size(4)));
@@ -0,0 +1,12 @@
+namespace first {
+union Value {
+    int i;
+    float f;
+};
+}
+
+namespace second {
+struct Value {
+    int i;
+};
+}
@@ -0,0 +1,4 @@
+union value {
+    _Atomic int counter;
+    float ratio;
+};
@@ -0,0 +1,4 @@
+union bits {
+    unsigned low : 4;
+    unsigned wide : 12;
+};
@@ -0,0 +1,4 @@
+union bar {
+    char c;
+    int i;
+};
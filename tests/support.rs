@@ -27,9 +27,7 @@ impl Logger for TestLogger {
     }
 }
 
-pub fn generate_bindings(mut options: BindgenOptions,
-                         filename: &str)
-                         -> Result<Vec<P<ast::Item>>, ()> {
+fn generate(mut options: BindgenOptions, filename: &str) -> Result<bindgen::Bindings, ()> {
     if filename.ends_with("hpp") {
         options.clang_args.push("-std=c++11".to_string());
         options.clang_args.push("-Wno-narrowing".to_string());
@@ -37,7 +35,37 @@ pub fn generate_bindings(mut options: BindgenOptions,
     options.clang_args.push(filename.to_string());
 
     let logger = TestLogger;
-    Ok(try!(bindgen::Bindings::generate(&options, Some(&logger as &Logger), None)).into_ast())
+    bindgen::Bindings::generate(&options, Some(&logger as &Logger), None)
+}
+
+pub fn generate_bindings(options: BindgenOptions,
+                         filename: &str)
+                         -> Result<Vec<P<ast::Item>>, ()> {
+    Ok(try!(generate(options, filename)).into_ast())
+}
+
+pub fn generate_static_wrappers(options: BindgenOptions, filename: &str) -> String {
+    let bindings = generate(options, &format!("tests/{}", filename)[..]).unwrap();
+    bindings.static_wrappers().to_owned()
+}
+
+/// For the generated code the AST can't represent, like unions.
+pub fn assert_bind_contains(options: BindgenOptions, filename: &str, expected: &[&str]) {
+    let generated = generate(options, &format!("tests/{}", filename)[..]).unwrap().to_string();
+    for e in expected {
+        if !generated.contains(e) {
+            println!("Generated bindings for {} do not contain `{}`:", filename, e);
+            println!("{}", generated);
+            panic!();
+        }
+    }
+}
+
+/// Compiles the generated code included in a module, as in
+/// `mod ffi { include!(...) }`, followed by the code using it.
+pub fn assert_compiles_in_module(options: BindgenOptions, filename: &str, user_code: &str) {
+    let generated = generate(options, &format!("tests/{}", filename)[..]).unwrap().to_string();
+    try_compile(&format!("mod ffi {{\n{}\n}}\n{}", generated, user_code));
 }
 
 pub fn assert_bind_eq(options: BindgenOptions,
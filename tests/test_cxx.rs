@@ -0,0 +1,88 @@
+use bindgen::Builder;
+
+use support::assert_bind_eq;
+
+#[test]
+fn class_in_namespace() {
+    assert_bind_eq(Default::default(), "headers/class.hpp", "
+        pub mod outer {
+            pub type point_t = inner::Point;
+            extern \"C\" {
+                #[link_name = \"_ZN5outer4drawEPKNS_5inner5PointE\"]
+                pub fn draw(p: *const inner::Point);
+            }
+            pub mod inner {
+                #[repr(C)]
+                #[derive(Debug)]
+                pub struct Point {
+                    pub x_: ::std::os::raw::c_int,
+                    pub y_: ::std::os::raw::c_int,
+                }
+                impl ::std::default::Default for Point {
+                    fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+                }
+                extern \"C\" {
+                    #[link_name = \"_ZN5outer5inner5PointC1Eii\"]
+                    pub fn Point_new(this: *mut Point, x: ::std::os::raw::c_int,
+                                     y: ::std::os::raw::c_int);
+                    #[link_name = \"_ZN5outer5inner5PointD1Ev\"]
+                    pub fn Point_destruct(this: *mut Point);
+                    #[link_name = \"_ZNK5outer5inner5Point1xEv\"]
+                    pub fn Point_x(this: *const Point) -> ::std::os::raw::c_int;
+                    #[link_name = \"_ZN5outer5inner5Point9translateEii\"]
+                    pub fn Point_translate(this: *mut Point, dx: ::std::os::raw::c_int,
+                                           dy: ::std::os::raw::c_int);
+                }
+                impl Point {
+                    #[inline]
+                    pub unsafe fn new(x: ::std::os::raw::c_int, y: ::std::os::raw::c_int) -> Self {
+                        let mut __bindgen_tmp = ::std::mem::MaybeUninit::<Self>::uninit();
+                        Point_new(__bindgen_tmp.as_mut_ptr(), x, y);
+                        __bindgen_tmp.assume_init()
+                    }
+                    #[inline]
+                    pub unsafe fn destruct(&mut self) {
+                        Point_destruct(self)
+                    }
+                    #[inline]
+                    pub unsafe fn x(&self) -> ::std::os::raw::c_int {
+                        Point_x(self)
+                    }
+                    #[inline]
+                    pub unsafe fn translate(&mut self, dx: ::std::os::raw::c_int,
+                                            dy: ::std::os::raw::c_int) {
+                        Point_translate(self, dx, dy)
+                    }
+                }
+            }
+        }
+    ");
+}
+
+#[test]
+fn non_trivial_classes() {
+    let bindings = Builder::new("tests/headers/class_non_trivial.hpp")
+        .generate()
+        .unwrap()
+        .to_string();
+    assert!(bindings.contains("#[derive(Copy, Clone)]\n#[derive(Debug)]\npub struct Trivial {"));
+    assert!(bindings.contains("pub fn make_trivial() -> Trivial;"));
+    assert!(bindings.contains("#[repr(C)]\n#[derive(Debug)]\npub struct Handle {"));
+    assert!(bindings.contains("#[repr(C)]\n#[derive(Debug)]\npub struct Holder {"));
+    assert!(!bindings.contains("open_handle"));
+    // Only `Trivial` can be copied, `Shape` having a vtable pointer.
+    assert!(bindings.contains("pub struct Shape {"));
+    assert_eq!(bindings.matches("#[derive(Copy, Clone)]").count(), 1);
+}
+
+#[test]
+fn opaque_non_trivial_class() {
+    let bindings = Builder::new("tests/headers/class_non_trivial.hpp")
+        .opaque_type("Handle")
+        .generate()
+        .unwrap()
+        .to_string();
+    assert!(bindings.contains("pub struct Handle {"));
+    assert!(!bindings.contains("impl ::std::clone::Clone for Handle"));
+    assert_eq!(bindings.matches("#[derive(Copy)]").count(), 0);
+}
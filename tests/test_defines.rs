@@ -1,5 +1,5 @@
-use bindgen::BindgenOptions;
-use support::assert_bind_eq;
+use bindgen::{BindgenOptions, Builder, FnMacroStyle};
+use support::{assert_bind_contains, assert_bind_eq, assert_compiles_in_module};
 
 #[test]
 fn extern_defines() {
@@ -12,3 +12,223 @@ fn extern_defines() {
         pub const ERROR: ::std::os::raw::c_char = -1;
     ");
 }
+
+#[test]
+fn literal_defines() {
+    let opts=BindgenOptions {
+        convert_macros: true,
+        ..Default::default()
+    };
+    assert_bind_eq(opts, "headers/defines_literals.h", "
+        pub const VERSION: &'static [u8; 4usize] = b\"1.2\\0\";
+        pub const PI: f64 = 3.14159;
+        pub const NEG_HALF: f64 = -0.5;
+        pub const SEP: ::std::os::raw::c_uchar = b'x';
+    ");
+}
+
+#[test]
+fn string_defines_as_str() {
+    let opts=BindgenOptions {
+        convert_macros: true,
+        macro_strings_as_str: true,
+        ..Default::default()
+    };
+    assert_bind_eq(opts, "headers/defines_literals.h", "
+        pub const VERSION: &'static str = \"1.2\";
+        pub const PI: f64 = 3.14159;
+        pub const NEG_HALF: f64 = -0.5;
+        pub const SEP: ::std::os::raw::c_uchar = b'x';
+    ");
+}
+
+#[test]
+fn fn_defines_as_macro_rules() {
+    let opts=BindgenOptions {
+        convert_macros: true,
+        fn_macros: FnMacroStyle::MacroRules,
+        ..Default::default()
+    };
+    assert_bind_eq(opts, "headers/defines_fn.h", "
+        pub const SHIFT: ::std::os::raw::c_uchar = 8;
+        #[macro_export]
+        macro_rules! MAKE_VERSION {
+            ($a:expr, $b:expr, $c:expr) => { ((($a) << 16) | (($b) << 8) | ($c)) };
+        }
+        #[macro_export]
+        macro_rules! INVERT {
+            ($x:expr) => { (!($x)) };
+        }
+        #[macro_export]
+        macro_rules! HALF {
+            ($x:expr) => { (($x) * 0.5) };
+        }
+    ");
+}
+
+#[test]
+fn fn_defines_as_macro_rules_in_module() {
+    let opts=BindgenOptions {
+        convert_macros: true,
+        fn_macros: FnMacroStyle::MacroRules,
+        ..Default::default()
+    };
+    assert_compiles_in_module(opts, "headers/defines_fn.h", "
+        pub fn version() -> u32 {
+            MAKE_VERSION!(1u32, 2u32, 3u32)
+        }
+    ");
+}
+
+#[test]
+fn fn_defines_as_inline_fns() {
+    let opts=BindgenOptions {
+        convert_macros: true,
+        fn_macros: FnMacroStyle::InlineFn,
+        ..Default::default()
+    };
+    assert_bind_eq(opts, "headers/defines_fn.h", "
+        pub const SHIFT: ::std::os::raw::c_uchar = 8;
+        #[inline]
+        pub fn MAKE_VERSION(a: i64, b: i64, c: i64) -> i64 {
+            (((a) << 16) | ((b) << (SHIFT as i64)) | (c))
+        }
+        #[inline]
+        pub fn INVERT(x: i64) -> i64 {
+            (!(x))
+        }
+    ");
+}
+
+#[test]
+fn typed_defines() {
+    let opts=BindgenOptions {
+        convert_macros: true,
+        macro_type_overrides: vec![("MODE_.*".to_owned(), "uint".to_owned())],
+        infer_macro_types: true,
+        ..Default::default()
+    };
+    assert_bind_eq(opts, "headers/defines_typed.h", "
+        pub type open_flags_t = ::std::os::raw::c_uint;
+        pub const OPEN_FLAGS_READ: open_flags_t = 1;
+        pub const OPEN_FLAGS_WRITE: open_flags_t = 2;
+        pub const MODE_FAST: ::std::os::raw::c_uint = 3;
+        pub const OTHER: ::std::os::raw::c_uchar = 4;
+    ");
+}
+
+#[test]
+fn typed_defines_not_fitting() {
+    let opts=BindgenOptions {
+        convert_macros: true,
+        macro_type_overrides: vec![("SMALL_.*".to_owned(), "uchar".to_owned())],
+        ..Default::default()
+    };
+    assert_bind_eq(opts, "headers/defines_typed_overflow.h", "
+        pub const SMALL_OK: ::std::os::raw::c_uchar = 16;
+        pub const SMALL_BIG: ::std::os::raw::c_ushort = 511;
+    ");
+}
+
+#[test]
+fn typed_defines_target_long() {
+    let mut opts=BindgenOptions {
+        convert_macros: true,
+        macro_type_overrides: vec![("BIG_.*".to_owned(), "slong".to_owned())],
+        ..Default::default()
+    };
+    opts.clang_args.push("--target=x86_64-unknown-linux-gnu".to_owned());
+    assert_bind_contains(opts, "headers/defines_typed_long.h", &[
+        "pub const BIG_OFFSET: ::std::os::raw::c_long = 4294967296;",
+    ]);
+
+    // `long` has 32 bits on Windows and on the 32-bit targets.
+    for target in &["--target=x86_64-pc-windows-msvc", "--target=i686-unknown-linux-gnu"] {
+        let bindings = Builder::new("tests/headers/defines_typed_long.h")
+            .clang_arg(*target)
+            .convert_macros(true)
+            .macro_type("BIG_.*", "slong")
+            .generate()
+            .unwrap()
+            .to_string();
+        assert!(!bindings.contains("c_long = 4294967296"));
+    }
+}
+
+#[test]
+fn grouped_defines() {
+    let opts=BindgenOptions {
+        convert_macros: true,
+        macro_type_overrides: vec![("LIB_OPEN_.*".to_owned(), "uint".to_owned())],
+        macro_groups: vec![("LIB_OPEN_".to_owned(), "OpenFlags".to_owned())],
+        macro_group_params: vec![("lib_open".to_owned(), "flags".to_owned(), "OpenFlags".to_owned())],
+        ..Default::default()
+    };
+    assert_bind_eq(opts, "headers/defines_group.h", "
+        #[repr(transparent)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub struct OpenFlags(pub ::std::os::raw::c_uint);
+        impl OpenFlags {
+            pub const READ: OpenFlags = OpenFlags(1);
+            pub const WRITE: OpenFlags = OpenFlags(2);
+            pub const CREATE: OpenFlags = OpenFlags(64);
+        }
+        impl ::std::ops::BitOr for OpenFlags {
+            type Output = Self;
+            #[inline]
+            fn bitor(self, other: Self) -> Self {
+                OpenFlags(self.0 | other.0)
+            }
+        }
+        impl ::std::ops::BitAnd for OpenFlags {
+            type Output = Self;
+            #[inline]
+            fn bitand(self, other: Self) -> Self {
+                OpenFlags(self.0 & other.0)
+            }
+        }
+        impl ::std::ops::Not for OpenFlags {
+            type Output = Self;
+            #[inline]
+            fn not(self) -> Self {
+                OpenFlags(!self.0)
+            }
+        }
+        pub const LIB_VERSION: ::std::os::raw::c_uchar = 3;
+        extern \"C\" {
+            pub fn lib_open(path: *const ::std::os::raw::c_char, flags: OpenFlags)
+             -> ::std::os::raw::c_int;
+        }
+    ");
+}
+
+#[test]
+fn grouped_defines_param_type() {
+    let opts=BindgenOptions {
+        convert_macros: true,
+        macro_groups: vec![("LIB_OPEN_".to_owned(), "OpenFlags".to_owned())],
+        macro_group_params: vec![("lib_open".to_owned(), "flags".to_owned(), "OpenFlags".to_owned())],
+        ..Default::default()
+    };
+    assert_bind_contains(opts, "headers/defines_group.h", &[
+        "pub struct OpenFlags(pub ::std::os::raw::c_uint);",
+        "pub const CREATE: OpenFlags = OpenFlags(64);",
+        "flags: OpenFlags",
+    ]);
+}
+
+#[test]
+fn grouped_defines_longest_prefix() {
+    let opts=BindgenOptions {
+        convert_macros: true,
+        macro_groups: vec![("LIB_OPEN_".to_owned(), "OpenFlags".to_owned()),
+                           ("LIB_OPEN_EX_".to_owned(), "OpenExFlags".to_owned())],
+        ..Default::default()
+    };
+    assert_bind_contains(opts, "headers/defines_group_nested.h", &[
+        "pub const READ: OpenFlags = OpenFlags(1);",
+        "pub const WRITE: OpenFlags = OpenFlags(2);",
+        "pub const SYNC: OpenExFlags = OpenExFlags(4);",
+        "pub const DIRECT: OpenExFlags = OpenExFlags(8);",
+    ]);
+}
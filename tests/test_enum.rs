@@ -145,3 +145,93 @@ fn with_overflowed_enum_value() {
         pub const Big: Bar = 2;
     ");
 }
+
+#[test]
+fn with_enum_styles() {
+    let options = BindgenOptions {
+        newtype_enums: vec!["Foo".to_owned()],
+        constified_enum_modules: vec!["Neg".to_owned()],
+        bitfield_enums: vec!["Flags".to_owned()],
+        .. Default::default()
+    };
+    assert_bind_eq(options, "headers/enum_styles.h", "
+        #[repr(transparent)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub struct Foo(pub u32);
+        impl Foo {
+            pub const Bar: Foo = Foo(0);
+            pub const Qux: Foo = Foo(1);
+        }
+        pub mod Neg {
+            pub type Type = i32;
+            pub const MinusOne: Type = -1;
+            pub const One: Type = 1;
+        }
+        #[repr(transparent)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub struct Flags(pub u32);
+        impl Flags {
+            pub const Read: Flags = Flags(1);
+            pub const Write: Flags = Flags(2);
+        }
+        impl ::std::ops::BitOr for Flags {
+            type Output = Self;
+            #[inline]
+            fn bitor(self, other: Self) -> Self {
+                Flags(self.0 | other.0)
+            }
+        }
+        impl ::std::ops::BitAnd for Flags {
+            type Output = Self;
+            #[inline]
+            fn bitand(self, other: Self) -> Self {
+                Flags(self.0 & other.0)
+            }
+        }
+        impl ::std::ops::Not for Flags {
+            type Output = Self;
+            #[inline]
+            fn not(self) -> Self {
+                Flags(!self.0)
+            }
+        }
+        extern \"C\" {
+            pub fn set_neg(n: Neg::Type);
+        }
+    ");
+}
+
+#[test]
+fn with_stripped_variant_prefixes() {
+    let options = BindgenOptions {
+        strip_enum_prefix: true,
+        camel_case_enum_variants: true,
+        .. Default::default()
+    };
+    assert_bind_eq(options, "headers/enum_prefix.h", "
+        #[derive(Copy, Clone)]
+        #[repr(u32)]
+        #[derive(Debug)]
+        pub enum color { Red = 0, DarkGreen = 1, }
+        #[derive(Copy, Clone)]
+        #[repr(u32)]
+        #[derive(Debug)]
+        pub enum mode { Mode1x = 0, ModeFast = 1, }
+    ");
+}
+
+#[test]
+fn with_stripped_variant_prefixes_as_consts() {
+    let options = BindgenOptions {
+        strip_enum_prefix: true,
+        .. default_without_rust_enums()
+    };
+    assert_bind_eq(options, "headers/enum_prefix_collision.h", "
+        pub type color = u32;
+        pub const COLOR_NONE: color = 0;
+        pub const COLOR_RED: color = 1;
+        pub type shape = u32;
+        pub const SHAPE_NONE: shape = 0;
+        pub const SHAPE_SQUARE: shape = 1;
+    ");
+}
@@ -0,0 +1,54 @@
+use bindgen::BindgenOptions;
+
+use support::assert_bind_eq;
+
+#[test]
+fn allowlist_function_pulls_dependencies() {
+    let mut options = BindgenOptions::default();
+    options.allowlist_functions.push("allowed_.*".into());
+    assert_bind_eq(options, "headers/allowlist.h", "
+        #[repr(C)]
+        #[derive(Copy, Clone)]
+        #[derive(Debug)]
+        pub struct inner {
+            pub x: ::std::os::raw::c_int,
+        }
+        impl ::std::default::Default for inner {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        #[repr(C)]
+        #[derive(Copy, Clone)]
+        #[derive(Debug)]
+        pub struct outer {
+            pub inner: *mut inner,
+        }
+        impl ::std::default::Default for outer {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        pub type outer_t = outer;
+        extern \"C\" {
+            pub fn allowed_fn(o: *mut outer_t) -> ::std::os::raw::c_int;
+        }
+    ");
+}
+
+#[test]
+fn blocklist() {
+    let mut options = BindgenOptions::default();
+    options.blocklist_types.push("(inner|outer|outer_t|unrelated)".into());
+    options.blocklist_functions.push("allowed_fn|other_fn".into());
+    assert_bind_eq(options, "headers/allowlist.h", "
+        #[repr(C)]
+        #[derive(Copy, Clone)]
+        #[derive(Debug)]
+        pub struct blocked {
+            pub z: ::std::os::raw::c_int,
+        }
+        impl ::std::default::Default for blocked {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        extern \"C\" {
+            pub fn blocked_fn(b: *mut blocked);
+        }
+    ");
+}
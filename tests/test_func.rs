@@ -1,4 +1,8 @@
-use support::assert_bind_eq;
+use std::fs;
+
+use bindgen::BindgenOptions;
+
+use support::{assert_bind_eq, generate_static_wrappers};
 
 #[test]
 fn func_ptr() {
@@ -93,3 +97,61 @@ fn with_array_arg() {
         }
     ");
 }
+
+#[test]
+fn static_inline() {
+    let mut options = BindgenOptions::default();
+    options.wrap_static_fns = true;
+    assert_bind_eq(options.clone(), "headers/func_static_inline.h", "
+        extern \"C\" {
+            #[link_name = \"add__extern\"]
+            pub fn add(a: ::std::os::raw::c_int, b: ::std::os::raw::c_int)
+             -> ::std::os::raw::c_int;
+            #[link_name = \"reset__extern\"]
+            pub fn reset(p: *mut ::std::os::raw::c_int);
+            pub fn exported() -> ::std::os::raw::c_int;
+        }
+    ");
+    let header = fs::canonicalize("tests/headers/func_static_inline.h").unwrap();
+    assert_eq!(generate_static_wrappers(options, "headers/func_static_inline.h"),
+               format!("/* automatically generated by rust-bindgen */\n\
+                        \n\
+                        #include \"{}\"\n\
+                        \n\
+                        __typeof__(int) add__extern(__typeof__(int) arg1, __typeof__(int) arg2) {{\n    \
+                            return add(arg1, arg2);\n\
+                        }}\n\
+                        __typeof__(void) reset__extern(__typeof__(int *) arg1) {{\n    \
+                            reset(arg1);\n\
+                        }}\n",
+                       header.display()));
+}
+
+#[test]
+fn static_inline_not_wrapped() {
+    assert_bind_eq(Default::default(), "headers/func_static_inline.h", "
+        extern \"C\" {
+            pub fn exported() -> ::std::os::raw::c_int;
+        }
+    ");
+}
+
+#[test]
+fn func_vla_params() {
+    assert_bind_eq(Default::default(), "headers/func_vla.h", r#"
+        extern "C" {
+            pub fn fill(n: ::std::os::raw::c_int, buf: *mut f64);
+        }
+    "#);
+}
+
+#[test]
+fn func_vla_2d_params() {
+    assert_bind_eq(Default::default(), "headers/func_vla_2d.h", r#"
+        extern "C" {
+            pub fn fill_grid(n: ::std::os::raw::c_int, m: ::std::os::raw::c_int,
+                             grid: *mut f64);
+            pub fn fill_rows(m: ::std::os::raw::c_int, rows: *mut f64);
+        }
+    "#);
+}
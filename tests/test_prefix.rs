@@ -1,6 +1,37 @@
-use bindgen::{Builder,BindgenOptions};
+use std::rc::Rc;
+
+use bindgen::{Builder,BindgenOptions,ItemKind,ParseCallbacks};
 use support::assert_bind_eq;
 
+#[derive(Debug)]
+struct Renamer;
+
+impl ParseCallbacks for Renamer {
+    fn item_name(&self, original: &str, kind: ItemKind) -> Option<String> {
+        match (kind, original) {
+            (ItemKind::Type, "point_t") => Some("Point".into()),
+            (ItemKind::Type, "color_t") => Some("Color".into()),
+            (ItemKind::Function, "GetColor") => Some("get_color".into()),
+            (ItemKind::Var, "GlobalCount") => Some("global_count".into()),
+            _ => None,
+        }
+    }
+
+    fn field_name(&self, parent: &str, field: &str) -> Option<String> {
+        match (parent, field) {
+            ("point_t", "x_pos") => Some("x".into()),
+            _ => None,
+        }
+    }
+
+    fn enum_variant_name(&self, enum_name: &str, variant: &str) -> Option<String> {
+        match (enum_name, variant) {
+            ("Color", "COLOR_RED") => Some("Red".into()),
+            _ => None,
+        }
+    }
+}
+
 #[test]
 fn remove_prefix() {
     let opts = BindgenOptions {
@@ -125,3 +156,34 @@ fn ignore_cyclic_references() {
     assert!(!bindings.contains("pub type struct_t = struct_t;"));
     assert!(!bindings.contains("pub type enum_t = enum_t;"));
 }
+
+#[test]
+fn rename_with_callbacks() {
+    let opts = BindgenOptions {
+        parse_callbacks: Some(Rc::new(Renamer)),
+        ..Default::default()
+    };
+    assert_bind_eq(opts, "headers/prefix_callbacks.h", "
+        #[repr(C)]
+        #[derive(Copy, Clone)]
+        #[derive(Debug)]
+        pub struct Point {
+            pub x: ::std::os::raw::c_int,
+        }
+        impl ::std::default::Default for Point {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        #[derive(Copy, Clone)]
+        #[repr(u32)]
+        #[derive(Debug)]
+        pub enum Color { Red = 0, }
+        extern \"C\" {
+            #[link_name = \"GlobalCount\"]
+            pub static mut global_count: ::std::os::raw::c_int;
+        }
+        extern \"C\" {
+            #[link_name = \"GetColor\"]
+            pub fn get_color(p: *mut Point) -> ::std::os::raw::c_int;
+        }
+    ");
+}
@@ -59,28 +59,32 @@ fn vectors() {
 
 #[test]
 fn i128() {
-    assert_bind_eq(Default::default(),
+    let mut options = bindgen::BindgenOptions::default();
+    options.clang_args.push("--target=x86_64-unknown-linux-gnu".to_owned());
+    assert_bind_eq(options,
                    "headers/i128.h",
                    "
-    #[repr(C)]
+    #[repr(C, align(16))]
     #[derive(Copy, Clone)]
     #[derive(Debug)]
-    pub struct int128 {
-        pub hi: ::std::os::raw::c_longlong,
+    pub struct __BindgenInt128 {
         pub lo: ::std::os::raw::c_ulonglong,
+        pub hi: ::std::os::raw::c_longlong,
     }
-    impl ::std::default::Default for int128 {
+    impl ::std::default::Default for __BindgenInt128 {
         fn default() -> Self { unsafe { ::std::mem::zeroed() } }
     }
-    #[repr(C)]
+    pub type int128 = __BindgenInt128;
+    #[repr(C, align(16))]
     #[derive(Copy, Clone)]
     #[derive(Debug)]
-    pub struct uint128 {
-        pub hi: ::std::os::raw::c_ulonglong,
+    pub struct __BindgenUInt128 {
         pub lo: ::std::os::raw::c_ulonglong,
+        pub hi: ::std::os::raw::c_ulonglong,
     }
-    impl ::std::default::Default for uint128 {
+    impl ::std::default::Default for __BindgenUInt128 {
         fn default() -> Self { unsafe { ::std::mem::zeroed() } }
     }
+    pub type uint128 = __BindgenUInt128;
     ");
 }
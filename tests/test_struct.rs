@@ -1,6 +1,7 @@
 use std::mem;
 
-use support::assert_bind_eq;
+use bindgen::{BindgenOptions, Builder};
+use support::{assert_bind_eq, assert_bind_contains};
 
 #[test]
 fn with_anon_enum() {
@@ -40,8 +41,27 @@ fn with_anon_enum_bitfields() {
         #[derive(Copy, Clone)]
         #[derive(Debug)]
         pub struct foo {
-            pub _bindgen_bitfield_1_: Enum_Unnamed1,
-            pub _bindgen_bitfield_2_: test,
+            pub _bindgen_bitfield_1_: u32,
+        }
+        impl foo {
+            #[inline]
+            pub fn bar(&self) -> Enum_Unnamed1 {
+                unsafe { ::std::mem::transmute((self._bindgen_bitfield_1_ << 28) >> 28) }
+            }
+            #[inline]
+            pub fn set_bar(&mut self, val: Enum_Unnamed1) {
+                let mask = 15u64 as u32;
+                self._bindgen_bitfield_1_ = (self._bindgen_bitfield_1_ & !mask) | (((val as u32) << 0) & mask);
+            }
+            #[inline]
+            pub fn baz(&self) -> test {
+                unsafe { ::std::mem::transmute((self._bindgen_bitfield_1_ << 24) >> 28) }
+            }
+            #[inline]
+            pub fn set_baz(&mut self, val: test) {
+                let mask = 240u64 as u32;
+                self._bindgen_bitfield_1_ = (self._bindgen_bitfield_1_ & !mask) | (((val as u32) << 4) & mask);
+            }
         }
         impl ::std::default::Default for foo {
             fn default() -> Self { unsafe { ::std::mem::zeroed() } }
@@ -312,6 +332,62 @@ fn with_bitfields() {
             pub _bindgen_bitfield_2_: ::std::os::raw::c_uint,
             pub _bindgen_bitfield_3_: ::std::os::raw::c_uint,
         }
+        impl bitfield {
+            #[inline]
+            pub fn a(&self) -> ::std::os::raw::c_ushort {
+                (self._bindgen_bitfield_1_ << 15) >> 15
+            }
+            #[inline]
+            pub fn set_a(&mut self, val: ::std::os::raw::c_ushort) {
+                let mask = 1u64 as ::std::os::raw::c_ushort;
+                self._bindgen_bitfield_1_ = (self._bindgen_bitfield_1_ & !mask) | (((val as ::std::os::raw::c_ushort) << 0) & mask);
+            }
+            #[inline]
+            pub fn b(&self) -> ::std::os::raw::c_ushort {
+                (self._bindgen_bitfield_1_ << 14) >> 15
+            }
+            #[inline]
+            pub fn set_b(&mut self, val: ::std::os::raw::c_ushort) {
+                let mask = 2u64 as ::std::os::raw::c_ushort;
+                self._bindgen_bitfield_1_ = (self._bindgen_bitfield_1_ & !mask) | (((val as ::std::os::raw::c_ushort) << 1) & mask);
+            }
+            #[inline]
+            pub fn c(&self) -> ::std::os::raw::c_ushort {
+                (self._bindgen_bitfield_1_ << 13) >> 15
+            }
+            #[inline]
+            pub fn set_c(&mut self, val: ::std::os::raw::c_ushort) {
+                let mask = 4u64 as ::std::os::raw::c_ushort;
+                self._bindgen_bitfield_1_ = (self._bindgen_bitfield_1_ & !mask) | (((val as ::std::os::raw::c_ushort) << 2) & mask);
+            }
+            #[inline]
+            pub fn d(&self) -> ::std::os::raw::c_ushort {
+                (self._bindgen_bitfield_1_ << 8) >> 14
+            }
+            #[inline]
+            pub fn set_d(&mut self, val: ::std::os::raw::c_ushort) {
+                let mask = 192u64 as ::std::os::raw::c_ushort;
+                self._bindgen_bitfield_1_ = (self._bindgen_bitfield_1_ & !mask) | (((val as ::std::os::raw::c_ushort) << 6) & mask);
+            }
+            #[inline]
+            pub fn f(&self) -> ::std::os::raw::c_uint {
+                (self._bindgen_bitfield_2_ << 30) >> 30
+            }
+            #[inline]
+            pub fn set_f(&mut self, val: ::std::os::raw::c_uint) {
+                let mask = 3u64 as ::std::os::raw::c_uint;
+                self._bindgen_bitfield_2_ = (self._bindgen_bitfield_2_ & !mask) | (((val as ::std::os::raw::c_uint) << 0) & mask);
+            }
+            #[inline]
+            pub fn g(&self) -> ::std::os::raw::c_uint {
+                (self._bindgen_bitfield_3_ << 0) >> 0
+            }
+            #[inline]
+            pub fn set_g(&mut self, val: ::std::os::raw::c_uint) {
+                let mask = 4294967295u64 as ::std::os::raw::c_uint;
+                self._bindgen_bitfield_3_ = (self._bindgen_bitfield_3_ & !mask) | (((val as ::std::os::raw::c_uint) << 0) & mask);
+            }
+        }
         impl ::std::default::Default for bitfield {
             fn default() -> Self { unsafe { ::std::mem::zeroed() } }
         }
@@ -409,15 +485,40 @@ fn derive_debug_big_array() {
 fn struct_with_incomplete_array() {
     assert_bind_eq(Default::default(), "headers/struct_with_incomplete_array.h", "
         #[repr(C)]
-        #[derive(Copy, Clone)]
         #[derive(Debug)]
         pub struct incomplete_array {
             pub x: ::std::os::raw::c_int,
-            pub y: [::std::os::raw::c_int; 0usize],
+            pub y: __BindgenFlexArray<::std::os::raw::c_int>,
         }
         impl ::std::default::Default for incomplete_array {
             fn default() -> Self { unsafe { ::std::mem::zeroed() } }
         }
+        /// A flexible array member, whose elements follow the struct.
+        #[repr(C)]
+        pub struct __BindgenFlexArray<T>([T; 0]);
+        impl<T> __BindgenFlexArray<T> {
+            #[inline]
+            pub unsafe fn as_ptr(&self) -> *const T {
+                self as *const __BindgenFlexArray<T> as *const T
+            }
+            #[inline]
+            pub unsafe fn as_mut_ptr(&mut self) -> *mut T {
+                self as *mut __BindgenFlexArray<T> as *mut T
+            }
+            #[inline]
+            pub unsafe fn as_slice(&self, len: usize) -> &[T] {
+                ::std::slice::from_raw_parts(self.as_ptr(), len)
+            }
+            #[inline]
+            pub unsafe fn as_mut_slice(&mut self, len: usize) -> &mut [T] {
+                ::std::slice::from_raw_parts_mut(self.as_mut_ptr(), len)
+            }
+        }
+        impl<T> ::std::fmt::Debug for __BindgenFlexArray<T> {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                f.write_str(\"__BindgenFlexArray\")
+            }
+        }
     ");
 }
 
@@ -443,14 +544,13 @@ fn struct_with_aligned_struct() {
         pub type int16_t = i16;
         pub type int32_t = i32;
         pub type int64_t = i64;
-        #[repr(C)]
+        #[repr(C, align(64))]
         #[derive(Copy, Clone)]
         #[derive(Debug)]
         pub struct foo {
             pub x: int32_t,
             pub y: int64_t,
             pub z: int16_t,
-            _bindgen_padding_0_: [u64; 5usize],
         }
         impl ::std::default::Default for foo {
             fn default() -> Self { unsafe { ::std::mem::zeroed() } }
@@ -467,14 +567,13 @@ fn struct_with_aligned_struct() {
         impl ::std::default::Default for bar {
             fn default() -> Self { unsafe { ::std::mem::zeroed() } }
         }
-		#[repr(C)]
+		#[repr(C, align(32))]
 		#[derive(Copy, Clone)]
 		#[derive(Debug)]
 		pub struct smaller_align {
 			pub x: int32_t,
 			pub y: int64_t,
 			pub z: int16_t,
-			_bindgen_padding_0_: [u8; 14usize],
 		}
 		impl ::std::default::Default for smaller_align {
 			fn default() -> Self { unsafe { ::std::mem::zeroed() } }
@@ -496,14 +595,13 @@ fn struct_with_aligned_struct() {
     pub type int16_t = ::std::os::raw::c_short;
     pub type int32_t = ::std::os::raw::c_int;
     pub type int64_t = ::std::os::raw::c_longlong;
-    #[repr(C)]
+    #[repr(C, align(64))]
     #[derive(Copy, Clone)]
     #[derive(Debug)]
     pub struct foo {
         pub x: int32_t,
         pub y: int64_t,
         pub z: int16_t,
-        _bindgen_padding_0_: [u64; 5usize],
     }
 
     #[repr(C)]
@@ -520,7 +618,7 @@ fn struct_with_aligned_struct() {
     assert_eq!(offset_of!(foo, x), 0);
     assert_eq!(offset_of!(foo, y), 8);
     assert_eq!(offset_of!(foo, z), 16);
-    assert_eq!(offset_of!(foo, _bindgen_padding_0_), 24);
+    assert_eq!(mem::align_of::<foo>(), 64);
 
     assert_eq!(mem::size_of::<bar>(), 128);
     assert_eq!(offset_of!(bar, a), 0);
@@ -528,3 +626,293 @@ fn struct_with_aligned_struct() {
     assert_eq!(offset_of!(bar, _bindgen_padding_0_), 16);
     assert_eq!(offset_of!(bar, foo), 64);
 }
+
+#[test]
+fn with_doc_comments() {
+    assert_bind_eq(Default::default(), "headers/struct_with_doc_comments.h", "
+        /// A point in the plane.
+        #[repr(C)]
+        #[derive(Copy, Clone)]
+        #[derive(Debug)]
+        pub struct point {
+            /// The horizontal coordinate.
+            pub x: ::std::os::raw::c_int,
+            /// The vertical coordinate.
+            pub y: ::std::os::raw::c_int,
+        }
+        impl ::std::default::Default for point {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        extern \"C\" {
+            /// \\brief Moves a point.
+            /// \\param p The point to move.
+            pub fn move_point(p: *mut point);
+        }
+    ");
+}
+
+#[test]
+fn with_doc_comments_stripped_doxygen() {
+    let mut options = BindgenOptions::default();
+    options.strip_doxygen = true;
+    assert_bind_contains(options, "headers/struct_with_doc_comments.h", &[
+        "/// Moves a point.\n///\n/// # Parameters\n///\n/// * `p` - The point to move.\n",
+    ]);
+}
+
+#[test]
+fn with_layout_test() {
+    let mut options = BindgenOptions::default();
+    options.layout_tests = true;
+    assert_bind_eq(options, "headers/struct_with_layout_test.h", "
+        #[repr(C)]
+        #[derive(Copy, Clone)]
+        #[derive(Debug)]
+        pub struct foo {
+            pub a: ::std::os::raw::c_char,
+            pub b: ::std::os::raw::c_int,
+            pub _bindgen_bitfield_1_: ::std::os::raw::c_uint,
+        }
+        impl foo {
+            #[inline]
+            pub fn c(&self) -> ::std::os::raw::c_uint {
+                (self._bindgen_bitfield_1_ << 28) >> 28
+            }
+            #[inline]
+            pub fn set_c(&mut self, val: ::std::os::raw::c_uint) {
+                let mask = 15u64 as ::std::os::raw::c_uint;
+                self._bindgen_bitfield_1_ = (self._bindgen_bitfield_1_ & !mask) | (((val as ::std::os::raw::c_uint) << 0) & mask);
+            }
+        }
+        impl ::std::default::Default for foo {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        #[test]
+        fn bindgen_test_layout_foo() {
+            assert_eq!(::std::mem::size_of::<foo>(), 12usize);
+            assert_eq!(::std::mem::align_of::<foo>(), 4usize);
+            let v: foo = ::std::default::Default::default();
+            assert_eq!(::std::ptr::addr_of!(v.a) as usize - &v as *const foo as usize,
+                       0usize);
+            assert_eq!(::std::ptr::addr_of!(v.b) as usize - &v as *const foo as usize,
+                       4usize);
+            assert_eq!(::std::ptr::addr_of!(v._bindgen_bitfield_1_) as usize -
+                       &v as *const foo as usize,
+                       8usize);
+        }
+    ");
+}
+
+#[test]
+fn with_field_offsets() {
+    assert_bind_contains(Default::default(), "headers/struct_with_field_offsets.h", &[
+        "#[repr(C, align(16))]\n#[derive(Copy, Clone)]\n#[derive(Debug)]\npub struct field_aligned {
+    pub a: ::std::os::raw::c_char,
+    _bindgen_padding_0_: [u8; 15usize],
+    pub b: ::std::os::raw::c_int,
+}",
+        "#[repr(C, align(4))]\n#[derive(Copy, Clone)]\n#[derive(Debug)]\npub struct bitfield_shared {
+    pub _bindgen_bitfield_1_: [u8; 1usize],
+    pub c: ::std::os::raw::c_char,
+    _bindgen_padding_0_: [u8; 2usize],
+}",
+        "pub fn a(&self) -> ::std::os::raw::c_uint",
+        "pub fn set_b(&mut self, val: ::std::os::raw::c_uint)",
+    ]);
+}
+
+#[test]
+fn with_pack_and_align() {
+    assert_bind_contains(Default::default(), "headers/struct_with_pack_and_align.h", &[
+        "#[repr(C, packed(2))]\n#[derive(Copy, Clone)]\n#[derive(Debug)]\npub struct packed2 {",
+        "#[repr(C, align(64))]\n#[derive(Copy, Clone)]\n#[derive(Debug)]\npub struct cache_line {",
+        "#[repr(C, align(4))]\n#[derive(Copy)]\npub struct packed_aligned {\n    _bindgen_opaque_blob: [u32; 2usize],\n}",
+    ]);
+}
+
+#[test]
+fn with_opaque_types() {
+    let mut options = BindgenOptions::default();
+    options.opaque_types = vec!["inner".to_owned(), "hidden_t".to_owned()];
+    assert_bind_eq(options, "headers/struct_opaque.h", "
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct inner {
+            _bindgen_opaque_blob: [u64; 2usize],
+        }
+        impl ::std::clone::Clone for inner {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for inner {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct outer {
+            pub i: inner,
+            pub c: ::std::os::raw::c_char,
+        }
+        impl ::std::clone::Clone for outer {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for outer {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct hidden_t {
+            _bindgen_opaque_blob: [u32; 1usize],
+        }
+        impl ::std::clone::Clone for hidden_t {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for hidden_t {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        extern \"C\" {
+            pub fn use_hidden(h: hidden_t);
+        }
+    ");
+}
+
+#[test]
+fn with_long_double() {
+    assert_bind_contains(Default::default(), "headers/struct_long_double.h", &[
+        "pub ld: __BindgenLongDouble,",
+        "#[repr(C, align(16))]\n#[derive(Copy)]\npub struct __BindgenLongDouble {",
+        "pub fn scale(x: __BindgenLongDouble) -> __BindgenLongDouble;",
+    ]);
+}
+
+#[test]
+fn with_int128_structs() {
+    let bindings = Builder::new("tests/headers/struct_int128.h")
+        .clang_arg("--target=x86_64-unknown-linux-gnu")
+        .generate()
+        .unwrap()
+        .to_string();
+    assert!(bindings.contains("pub a: __BindgenInt128,"));
+    assert!(bindings.contains("pub b: __BindgenInt128,"));
+    assert!(bindings.contains("pub c: __BindgenUInt128,"));
+    assert_eq!(bindings.matches("pub struct __BindgenInt128 {").count(), 1);
+    assert_eq!(bindings.matches("pub struct __BindgenUInt128 {").count(), 1);
+    assert!(bindings.contains("#[repr(C, align(16))]\n#[derive(Copy, Clone)]\n#[derive(Debug)]\n\
+                               pub struct __BindgenInt128 {\n    \
+                               pub lo: ::std::os::raw::c_ulonglong,\n    \
+                               pub hi: ::std::os::raw::c_longlong,\n}"));
+}
+
+#[test]
+fn with_native_int128() {
+    let mut options = BindgenOptions::default();
+    options.native_int128 = true;
+    assert_bind_contains(options, "headers/struct_int128.h", &[
+        "pub a: i128,",
+        "pub b: i128,",
+        "pub c: u128,",
+    ]);
+}
+
+#[test]
+fn with_flexible_array_member() {
+    let mut options = BindgenOptions::default();
+    options.flexible_array_lens.push(("msg".to_owned(), "len".to_owned()));
+    assert_bind_contains(options, "headers/struct_flex_array.h", &[
+        "pub data: __BindgenFlexArray<::std::os::raw::c_char>,",
+        "pub unsafe fn data(&self) -> &[::std::os::raw::c_char] {\n",
+        "self.data.as_slice(self.len as usize)",
+        "pub struct __BindgenFlexArray<T>([T; 0]);",
+        "pub unsafe fn as_mut_slice(&mut self, len: usize) -> &mut [T] {\n",
+    ]);
+}
+
+#[test]
+fn with_unsupported_field() {
+    let options = BindgenOptions {
+        fail_on_unknown_type: false,
+        ..Default::default()
+    };
+    assert_bind_contains(options, "headers/struct_unsupported.hpp", &[
+        "pub member: __BindgenUnsupported8x8,",
+        "pub after: ::std::os::raw::c_int,",
+        "pub struct __BindgenUnsupported8x8 {",
+        "_bindgen_opaque_blob: [u64; 1usize],",
+    ]);
+}
+
+#[test]
+fn with_complex() {
+    assert_bind_contains(Default::default(), "headers/struct_complex.h", &[
+        "pub gain: __BindgenComplex<f32>,",
+        "pub samples: [__BindgenComplex<f64>; 4usize],",
+        "y: *const __BindgenComplex<f64>",
+        "-> __BindgenComplex<f64>;",
+        "pub static mut unit: __BindgenComplex<f32>;",
+        "pub struct __BindgenComplex<T> {",
+    ]);
+}
+
+#[test]
+fn with_complex_type() {
+    let bindings = Builder::new("tests/headers/struct_complex.h")
+        .complex_type("num_complex::Complex")
+        .generate()
+        .unwrap()
+        .to_string();
+    assert!(bindings.contains("pub gain: ::num_complex::Complex<f32>,"));
+    assert!(bindings.contains("pub static mut unit: ::num_complex::Complex<f32>;"));
+    assert!(!bindings.contains("__BindgenComplex"));
+}
+
+#[test]
+fn with_atomics() {
+    let bindings = Builder::new("tests/headers/struct_atomic.h")
+        .generate()
+        .unwrap()
+        .to_string();
+    assert!(bindings.contains("pub state: ::std::sync::atomic::AtomicI32,"));
+    assert!(bindings.contains("pub total: ::std::sync::atomic::AtomicU64,"));
+    assert!(bindings.contains("pub hits: ::std::sync::atomic::AtomicU32,"));
+    assert!(bindings.contains("pub next: ::std::sync::atomic::AtomicPtr<Shared>,"));
+    assert!(bindings.contains("pub ready: ::std::sync::atomic::AtomicBool,"));
+    assert!(bindings.contains("pub pair: __BindgenUnsupported8x8,"));
+    assert!(bindings.contains("pub pending: ::std::sync::atomic::AtomicPtr<counter_t>,"));
+    // The pointee of a pointer to pointer can't be resolved.
+    assert!(bindings.contains("pub slots: __BindgenUnsupported8x8,"));
+    // Bound, with a warning about its ABI.
+    assert!(bindings.contains("pub fn publish(pair: __BindgenUnsupported8x8);"));
+    // Only `Pair` derives them, `Shared` can't.
+    assert_eq!(bindings.matches("#[derive(Copy, Clone)]").count(), 1);
+    assert!(!bindings.contains("impl ::std::clone::Clone for Shared"));
+}
+
+#[test]
+fn with_atomic_char_and_typedef() {
+    let x86_64 = Builder::new("tests/headers/struct_atomic_char.h")
+        .clang_arg("--target=x86_64-unknown-linux-gnu")
+        .generate()
+        .unwrap()
+        .to_string();
+    assert!(x86_64.contains("pub flag: ::std::sync::atomic::AtomicI8,"));
+    assert!(x86_64.contains("pub type atomic_count_t = ::std::sync::atomic::AtomicI32;"));
+    assert!(x86_64.contains("pub count: atomic_count_t,"));
+
+    // A plain `char` is unsigned on ARM.
+    let arm = Builder::new("tests/headers/struct_atomic_char.h")
+        .clang_arg("--target=armv7-unknown-linux-gnueabihf")
+        .generate()
+        .unwrap()
+        .to_string();
+    assert!(arm.contains("pub flag: ::std::sync::atomic::AtomicU8,"));
+}
+
+#[test]
+fn with_keyword_bitfield() {
+    let bindings = Builder::new("tests/headers/struct_bitfield_keyword.h")
+        .generate()
+        .unwrap()
+        .to_string();
+    assert!(bindings.contains("pub fn type_(&self) -> ::std::os::raw::c_uint {"));
+    assert!(bindings.contains("pub fn set_type_(&mut self, val: ::std::os::raw::c_uint) {"));
+    assert!(bindings.contains("pub fn set_mode(&mut self, val: ::std::os::raw::c_uint) {"));
+}
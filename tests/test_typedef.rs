@@ -1,4 +1,5 @@
-use support::assert_bind_eq;
+use bindgen::BindgenOptions;
+use support::{assert_bind_eq, assert_bind_contains};
 
 #[test]
 fn typedef_same_name() {
@@ -8,3 +9,43 @@ fn typedef_same_name() {
         pub enum SameU {  }
     ");
 }
+
+#[test]
+fn typedef_vectors() {
+    assert_bind_contains(Default::default(), "headers/typedef_vectors.h", &[
+        "pub type v4si = __BindgenVector16<[::std::os::raw::c_int; 4usize]>;",
+        "pub type v4sf = __BindgenVector16<[::std::os::raw::c_float; 4usize]>;",
+        "pub type v2hi = __BindgenVector4<[::std::os::raw::c_short; 2usize]>;",
+        "#[repr(C, align(16))]\n#[derive(Copy, Clone, Debug)]\npub struct __BindgenVector16<T>(pub T);",
+    ]);
+}
+
+#[test]
+fn typedef_arch_vectors() {
+    let mut options = BindgenOptions::default();
+    options.arch_vector_types = true;
+    options.clang_args.push("--target=x86_64-unknown-linux-gnu".to_owned());
+    assert_bind_contains(options, "headers/typedef_vectors.h", &[
+        "pub type v4si = ::std::arch::x86_64::__m128i;",
+        "pub type v4sf = ::std::arch::x86_64::__m128;",
+        "pub type v2hi = __BindgenVector4<[::std::os::raw::c_short; 2usize]>;",
+    ]);
+}
+
+#[test]
+fn typedef_arch_vectors_other_targets() {
+    let mut options = BindgenOptions::default();
+    options.arch_vector_types = true;
+    options.clang_args.push("-target".to_owned());
+    options.clang_args.push("i686-unknown-linux-gnu".to_owned());
+    assert_bind_contains(options, "headers/typedef_vectors.h", &[
+        "pub type v4si = ::std::arch::x86::__m128i;",
+    ]);
+
+    let mut options = BindgenOptions::default();
+    options.arch_vector_types = true;
+    options.clang_args.push("--target=aarch64-unknown-linux-gnu".to_owned());
+    assert_bind_contains(options, "headers/typedef_vectors.h", &[
+        "pub type v4si = __BindgenVector16<[::std::os::raw::c_int; 4usize]>;",
+    ]);
+}
@@ -1,4 +1,6 @@
-use support::assert_bind_eq;
+use bindgen::{BindgenOptions, Builder};
+
+use support::{assert_bind_eq, assert_bind_contains};
 
 #[test]
 fn with_anon_struct() {
@@ -46,6 +48,24 @@ fn with_anon_struct_bitfield() {
                 let raw: *mut u8 = ::std::mem::transmute(&self._bindgen_data_);
                 ::std::mem::transmute(raw.offset(0))
             }
+            #[inline]
+            pub fn b(&self) -> ::std::os::raw::c_int {
+                unsafe { (*(::std::mem::transmute::<_, *const u8>(&self._bindgen_data_).offset(0) as *const ::std::os::raw::c_int) << 25) >> 25 }
+            }
+            #[inline]
+            pub fn set_b(&mut self, val: ::std::os::raw::c_int) {
+                let mask = 127u64 as ::std::os::raw::c_int;
+                unsafe { *(::std::mem::transmute::<_, *mut u8>(&mut self._bindgen_data_).offset(0) as *mut ::std::os::raw::c_int) = (*(::std::mem::transmute::<_, *const u8>(&self._bindgen_data_).offset(0) as *const ::std::os::raw::c_int) & !mask) | (((val as ::std::os::raw::c_int) << 0) & mask); }
+            }
+            #[inline]
+            pub fn c(&self) -> ::std::os::raw::c_int {
+                unsafe { (*(::std::mem::transmute::<_, *const u8>(&self._bindgen_data_).offset(0) as *const ::std::os::raw::c_int) << 0) >> 7 }
+            }
+            #[inline]
+            pub fn set_c(&mut self, val: ::std::os::raw::c_int) {
+                let mask = 4294967168u64 as ::std::os::raw::c_int;
+                unsafe { *(::std::mem::transmute::<_, *mut u8>(&mut self._bindgen_data_).offset(0) as *mut ::std::os::raw::c_int) = (*(::std::mem::transmute::<_, *const u8>(&self._bindgen_data_).offset(0) as *const ::std::os::raw::c_int) & !mask) | (((val as ::std::os::raw::c_int) << 7) & mask); }
+            }
         }
         impl ::std::default::Default for foo {
             fn default() -> Self { unsafe { ::std::mem::zeroed() } }
@@ -243,3 +263,106 @@ fn with_derive_debug() {
         }
     ");
 }
+
+#[test]
+fn rust_union_with_anon_struct() {
+    let mut options: BindgenOptions = Default::default();
+    options.rust_unions = true;
+    assert_bind_contains(options, "headers/union_with_anon_struct.h", &[
+        "pub union foo {",
+        "pub bar: Struct_Unnamed1,",
+        "pub struct Struct_Unnamed1 {",
+    ]);
+}
+
+#[test]
+fn rust_union_with_anon_unnamed_union() {
+    let mut options: BindgenOptions = Default::default();
+    options.rust_unions = true;
+    assert_bind_contains(options, "headers/union_with_anon_unnamed_union.h", &[
+        "pub union foo {",
+        "pub a: ::std::os::raw::c_uint,",
+        "pub _bindgen_data_1_: Union_Unnamed1,",
+        "pub union Union_Unnamed1 {",
+        "pub b: ::std::os::raw::c_ushort,",
+    ]);
+}
+
+#[test]
+fn rust_union_with_layout_test() {
+    let mut options: BindgenOptions = Default::default();
+    options.rust_unions = true;
+    options.layout_tests = true;
+    assert_bind_contains(options, "headers/union_with_layout_test.h", &[
+        "pub union bar {",
+        "fn bindgen_test_layout_bar() {",
+        "let v: bar = ::std::default::Default::default();",
+        "0usize);",
+    ]);
+}
+
+#[test]
+fn rust_union_falls_back_to_blob() {
+    // Native unions can only hold `Copy` fields, which atomics are not.
+    let bindings = Builder::new("tests/headers/union_with_atomic.h")
+        .rust_unions(true)
+        .generate()
+        .unwrap()
+        .to_string();
+    assert!(bindings.contains("pub struct value {"));
+    assert!(bindings.contains("pub _bindgen_data_: [u32; 1usize],"));
+    assert!(bindings.contains("pub unsafe fn counter(&mut self) -> *mut ::std::sync::atomic::AtomicI32 {"));
+    assert!(!bindings.contains("pub union"));
+}
+
+#[test]
+fn rust_union_in_namespace() {
+    let bindings = Builder::new("tests/headers/union_namespaced.hpp")
+        .rust_unions(true)
+        .generate()
+        .unwrap()
+        .to_string();
+    assert_eq!(bindings.matches("pub union Value {").count(), 1);
+    assert_eq!(bindings.matches("pub struct Value {").count(), 1);
+    let first = bindings.find("pub mod first {").unwrap();
+    let second = bindings.find("pub mod second {").unwrap();
+    let union = bindings.find("pub union Value {").unwrap();
+    assert!(first < union && union < second);
+    assert!(!bindings.contains("bindgen_union"));
+}
+
+#[test]
+fn with_bitfields() {
+    // Every bitfield of a union starts at its first bit.
+    assert_bind_eq(Default::default(), "headers/union_with_bitfields.h", "
+        #[repr(C)]
+        #[derive(Copy, Clone)]
+        #[derive(Debug)]
+        pub struct bits {
+            pub _bindgen_data_: [u32; 1usize],
+        }
+        impl bits {
+            #[inline]
+            pub fn low(&self) -> ::std::os::raw::c_uint {
+                unsafe { (*(::std::mem::transmute::<_, *const u8>(&self._bindgen_data_).offset(0) as *const ::std::os::raw::c_uint) << 28) >> 28 }
+            }
+            #[inline]
+            pub fn set_low(&mut self, val: ::std::os::raw::c_uint) {
+                let mask = 15u64 as ::std::os::raw::c_uint;
+                unsafe { *(::std::mem::transmute::<_, *mut u8>(&mut self._bindgen_data_).offset(0) as *mut ::std::os::raw::c_uint) = (*(::std::mem::transmute::<_, *const u8>(&self._bindgen_data_).offset(0) as *const ::std::os::raw::c_uint) & !mask) | (((val as ::std::os::raw::c_uint) << 0) & mask); }
+            }
+            #[inline]
+            pub fn wide(&self) -> ::std::os::raw::c_uint {
+                unsafe { (*(::std::mem::transmute::<_, *const u8>(&self._bindgen_data_).offset(0) as *const ::std::os::raw::c_uint) << 20) >> 20 }
+            }
+            #[inline]
+            pub fn set_wide(&mut self, val: ::std::os::raw::c_uint) {
+                let mask = 4095u64 as ::std::os::raw::c_uint;
+                unsafe { *(::std::mem::transmute::<_, *mut u8>(&mut self._bindgen_data_).offset(0) as *mut ::std::os::raw::c_uint) = (*(::std::mem::transmute::<_, *const u8>(&self._bindgen_data_).offset(0) as *const ::std::os::raw::c_uint) & !mask) | (((val as ::std::os::raw::c_uint) << 0) & mask); }
+            }
+        }
+        impl ::std::default::Default for bits {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+    ");
+}
@@ -22,3 +22,5 @@ mod test_primitives;
 mod test_typedef;
 mod test_keywords;
 mod test_prefix;
+mod test_filter;
+mod test_cxx;